@@ -0,0 +1,461 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::io::{self, Write};
+
+use anyhow::{anyhow, bail, Context, Result};
+use tauri::Url;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::app::client_api::{api_endpoints, Client};
+use crate::app::gui::commands::{clear_data, collect_mods, load_builds, resolve_build_id};
+use crate::app::instance::Instances;
+use crate::app::options::Options;
+use crate::minecraft::auth::MinecraftAccount;
+use crate::minecraft::cache::BuildCache;
+use crate::minecraft::crash::CrashReport;
+use crate::minecraft::java::output::{OutputLine, OutputStream};
+use crate::minecraft::launcher::{gc, LauncherData, LauncherFrontend, StartParameter};
+use crate::minecraft::prelauncher;
+use crate::minecraft::progress::ProgressUpdate;
+use crate::LAUNCHER_DIRECTORY;
+
+const USAGE: &str = "\
+Usage: liquidlauncher [COMMAND]
+
+Starts the launcher window when no command is given.
+
+Commands:
+  launch       Launch the client without opening the launcher window
+                 --build <id>         Build to launch (defaults to the selected or latest build)
+                 --account <name>     Stored account to launch with, fails if it does not match
+                 --offline-account <name>
+                                      Launch with a new offline account of that name
+                 --data-dir <path>    Data directory to use instead of the configured one
+                 --instance <name>    Instance to launch, by name or id
+                 --dry-run            Print the resolved launch plan as JSON instead of launching
+  list-builds  List available builds
+                 --nightly            Include development builds
+  list-mods    List recommended and custom mods of a build
+                 --build <id>         Build to list the mods of (defaults to the selected or latest build)
+  clear-data   Remove downloaded assets, libraries, runtimes and game files
+                 --data-dir <path>    Data directory to clear instead of the configured one
+  gc           Remove libraries, assets, versions and runtimes no launched build uses
+                 --data-dir <path>    Data directory to clean up instead of the configured one
+                 --dry-run            Only print how much space would be reclaimed
+  help         Print this message";
+
+pub enum CliCommand {
+    Launch {
+        build_id: Option<u32>,
+        account: Option<String>,
+        offline_account: Option<String>,
+        data_dir: Option<String>,
+        instance: Option<String>,
+        dry_run: bool,
+    },
+    ListBuilds {
+        nightly: bool,
+    },
+    ListMods {
+        build_id: Option<u32>,
+    },
+    ClearData {
+        data_dir: Option<String>,
+    },
+    Gc {
+        data_dir: Option<String>,
+        dry_run: bool,
+    },
+    Help,
+}
+
+impl CliCommand {
+    /// Parses the command line arguments (without the program name).
+    ///
+    /// Returns [None] if no command was given, in which case the GUI should be started.
+    /// An unknown command is an error, instead of silently starting the GUI.
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>> {
+        let mut command = match args.next().as_deref() {
+            Some("launch") => CliCommand::Launch {
+                build_id: None,
+                account: None,
+                offline_account: None,
+                data_dir: None,
+                instance: None,
+                dry_run: false,
+            },
+            Some("list-builds") => CliCommand::ListBuilds { nightly: false },
+            Some("list-mods") => CliCommand::ListMods { build_id: None },
+            Some("clear-data") => CliCommand::ClearData { data_dir: None },
+            Some("gc") => CliCommand::Gc {
+                data_dir: None,
+                dry_run: false,
+            },
+            Some("help" | "--help" | "-h") => return Ok(Some(CliCommand::Help)),
+            // macOS passes the process serial number to apps started from the Finder on older versions
+            Some(arg) if arg.starts_with("-psn_") => return Ok(None),
+            Some(arg) => bail!("Unknown command: {}\n\n{}", arg, USAGE),
+            None => return Ok(None),
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("Missing value for argument {}\n\n{}", arg, USAGE))
+            };
+
+            match (&mut command, arg.as_str()) {
+                (
+                    CliCommand::Launch { build_id, .. } | CliCommand::ListMods { build_id },
+                    "--build",
+                ) => {
+                    let id = value()?;
+                    *build_id = Some(id.parse().with_context(|| format!("Invalid build id: {}", id))?);
+                }
+                (CliCommand::Launch { account, .. }, "--account") => *account = Some(value()?),
+                (CliCommand::Launch { offline_account, .. }, "--offline-account") => {
+                    *offline_account = Some(value()?)
+                }
+                (CliCommand::Launch { instance, .. }, "--instance") => *instance = Some(value()?),
+                (
+                    CliCommand::Launch { dry_run, .. } | CliCommand::Gc { dry_run, .. },
+                    "--dry-run",
+                ) => *dry_run = true,
+                (
+                    CliCommand::Launch { data_dir, .. }
+                    | CliCommand::ClearData { data_dir }
+                    | CliCommand::Gc { data_dir, .. },
+                    "--data-dir",
+                ) => *data_dir = Some(value()?),
+                (CliCommand::ListBuilds { nightly }, "--nightly") => *nightly = true,
+                _ => bail!("Unknown argument: {}\n\n{}", arg, USAGE),
+            }
+        }
+
+        Ok(Some(command))
+    }
+}
+
+/// Runs a command line command and returns when it is done.
+pub fn cli_main(command: CliCommand) -> Result<()> {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(command))
+}
+
+async fn run(command: CliCommand) -> Result<()> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let mut options = Options::load(config_dir).await.unwrap_or_default();
+
+    match command {
+        CliCommand::Launch {
+            build_id,
+            account,
+            offline_account,
+            data_dir,
+            instance,
+            dry_run,
+        } => {
+            let offline_account = match (account, offline_account) {
+                (Some(_), Some(_)) => bail!("--account and --offline-account can't be combined\n\n{}", USAGE),
+                (Some(name), None) => {
+                    let stored = options
+                        .start_options
+                        .minecraft_account
+                        .as_ref()
+                        .map(|account| account.get_username());
+
+                    // A typo must not launch with an offline account by accident
+                    if stored != Some(name.as_str()) {
+                        bail!(
+                            "Account {} is not stored, the stored account is {}. Use --offline-account to launch with an offline account.",
+                            name,
+                            stored.unwrap_or("none")
+                        );
+                    }
+                    None
+                }
+                (None, Some(name)) => Some(MinecraftAccount::auth_offline(name).await),
+                (None, None) => None,
+            };
+
+            let instance = match instance {
+                Some(name) => {
+                    let instances = Instances::load(config_dir)
+                        .await
+                        .context("unable to load instances")?;
+                    let instance = instances
+                        .find(&name)
+                        .with_context(|| format!("instance {} does not exist", name))?;
+                    Some(instance.clone())
+                }
+                None => None,
+            };
+
+            let session_token = options.launcher_options.session_token.clone();
+            let offline_endpoint = api_endpoints(&options.launcher_options.api_url).remove(0);
+            let client = if options.launcher_options.offline_mode {
+                Client::offline(&offline_endpoint, session_token)
+            } else {
+                match lookup(&options).await {
+                    Ok(client) => client,
+                    Err(e) => {
+                        warn!("{:?}", e);
+                        eprintln!("LiquidBounce API is not reachable, launching offline");
+                        options.launcher_options.offline_mode = true;
+                        Client::offline(&offline_endpoint, session_token)
+                    }
+                }
+            };
+
+            // Refresh the session and store it, the same way the launcher window does before launching
+            if !options.launcher_options.offline_mode && offline_account.is_none() {
+                if let Some(account) = options.start_options.minecraft_account.take() {
+                    options.start_options.minecraft_account =
+                        Some(account.refresh().await.context("unable to refresh account")?);
+                    options
+                        .store(config_dir)
+                        .await
+                        .context("unable to store config data")?;
+                }
+            }
+
+            // Not stored, the account, data directory and instance settings only apply to this launch
+            if let Some(account) = offline_account {
+                options.start_options.minecraft_account = Some(account);
+            }
+            if let Some(data_dir) = data_dir {
+                options.start_options.custom_data_path = data_dir;
+            }
+            if let Some(instance) = &instance {
+                instance.apply(&mut options);
+            }
+
+            let build_id = resolve_build_id(&client, &options, build_id).await?;
+            let branch_options = options.version_options.options.clone();
+
+            let mut parameters = StartParameter::from_options(client, options).await?;
+            parameters.instance_directory = instance
+                .as_ref()
+                .map(|instance| instance.game_directory(&parameters.data_directory()));
+            let instance_id = instance.as_ref().map(|instance| instance.id.clone());
+            parameters.instance_name = instance.map(|instance| instance.name);
+
+            info!("Loading launch manifest...");
+            let launch_manifest = prelauncher::load_launch_manifest(build_id, &parameters).await?;
+            // Offline, the mods of the last launch of the instance or the build are used
+            let cache = BuildCache::new(&parameters.data_directory(), build_id);
+            let mods = if parameters.offline_mode {
+                match &instance_id {
+                    Some(id) => cache.load_instance_mods(id).await,
+                    None => cache.load_mods().await,
+                }
+                .unwrap_or_default()
+            } else {
+                let mods = collect_mods(&parameters.client, &parameters.data_directory(), &branch_options, &launch_manifest.build).await?;
+                if let Some(id) = &instance_id {
+                    if let Err(e) = cache.store_instance_mods(id, &mods).await {
+                        warn!("Failed to cache mods of instance {}: {:?}", id, e);
+                    }
+                }
+                mods
+            };
+
+            if dry_run {
+                let (_, terminator_rx) = oneshot::channel();
+                let launcher_data = LauncherData {
+                    data: Box::new(Console),
+                    terminator: terminator_rx,
+                };
+
+                let plan =
+                    prelauncher::dry_run(launch_manifest, &parameters, mods, &launcher_data).await?;
+                println!("{}", serde_json::to_string_pretty(&plan)?);
+                return Ok(());
+            }
+
+            // Wait for the client to exit, instead of exiting right away
+            parameters.keep_launcher_open = true;
+
+            let (terminator_tx, terminator_rx) = oneshot::channel();
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    let _ = terminator_tx.send(());
+                }
+            });
+
+            let launcher_data = LauncherData {
+                data: Box::new(Console),
+                terminator: terminator_rx,
+            };
+
+            prelauncher::launch(launch_manifest, parameters, mods, launcher_data).await?;
+        }
+        CliCommand::ListBuilds { nightly } => {
+            let client = lookup(&options).await?;
+            let release = !nightly && !options.launcher_options.show_nightly_builds;
+            let builds = load_builds(&client, &options, release).await.map_err(|e| anyhow!(e))?;
+
+            for build in builds {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    build.build_id,
+                    build.lb_version,
+                    build.mc_version,
+                    build.branch,
+                    if build.release { "release" } else { "nightly" },
+                    build.date.format("%Y-%m-%d %H:%M")
+                );
+            }
+        }
+        CliCommand::ListMods { build_id } => {
+            let client = lookup(&options).await?;
+            let build_id = resolve_build_id(&client, &options, build_id).await?;
+            let launch_manifest = client
+                .fetch_launch_manifest(build_id)
+                .await
+                .with_context(|| format!("failed to fetch launch manifest of build {}", build_id))?;
+            let branch_options = &options.version_options.options;
+
+            let data = options.start_options.data_directory();
+
+            for current_mod in collect_mods(&client, &data, branch_options, &launch_manifest.build).await? {
+                println!(
+                    "{}\t{}",
+                    current_mod.name,
+                    if current_mod.required {
+                        "required"
+                    } else if current_mod.enabled {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+            }
+        }
+        CliCommand::ClearData { data_dir } => {
+            if let Some(data_dir) = data_dir {
+                options.start_options.custom_data_path = data_dir;
+            }
+
+            let data_directory = options.start_options.data_directory();
+            clear_data(options).await.map_err(|e| anyhow!(e))?;
+            println!("Cleared data in {}", data_directory.display());
+        }
+        CliCommand::Gc { data_dir, dry_run } => {
+            if let Some(data_dir) = data_dir {
+                options.start_options.custom_data_path = data_dir;
+            }
+
+            let instances = Instances::load(config_dir)
+                .await
+                .context("unable to load instances")?;
+            let report = gc::collect_garbage(
+                &options.start_options.data_directory(),
+                &instances.java_distributions(&options),
+                dry_run,
+            )
+            .await?;
+
+            for (category, reclaimable) in [
+                ("libraries", &report.libraries),
+                ("assets", &report.assets),
+                ("versions", &report.versions),
+                ("runtimes", &report.runtimes),
+            ] {
+                println!("{}\t{}\t{}", category, reclaimable.entries, reclaimable.bytes);
+            }
+            println!(
+                "{} {} bytes",
+                if dry_run { "Reclaimable:" } else { "Reclaimed:" },
+                report.total_bytes()
+            );
+        }
+        CliCommand::Help => println!("{}", USAGE),
+    }
+
+    Ok(())
+}
+
+async fn lookup(options: &Options) -> Result<Client> {
+    Client::lookup(
+        options.launcher_options.session_token.clone(),
+        &options.launcher_options.api_url,
+    )
+        .await
+        .map_err(|e| anyhow!("Unable to connect to LiquidBounce API\n\n{}", e))
+}
+
+/// Front-end of the command line, writes everything to the console.
+#[derive(Clone)]
+struct Console;
+
+impl LauncherFrontend for Console {
+    fn on_output(&self, line: &OutputLine) -> Result<()> {
+        // Passed through as the game printed it, logging it as well would repeat warnings on stderr
+        match line.stream {
+            OutputStream::Stderr => writeln!(io::stderr().lock(), "{}", line.text)?,
+            _ => writeln!(io::stdout().lock(), "{}", line.text)?,
+        }
+        Ok(())
+    }
+
+    fn on_progress(&self, progress_update: ProgressUpdate) -> Result<()> {
+        if let ProgressUpdate::SetLabel(label) = progress_update {
+            self.on_log(&label)?;
+        }
+        Ok(())
+    }
+
+    fn on_log(&self, msg: &str) -> Result<()> {
+        info!("{}", msg);
+        // Keeps stdout to the game output and the printed results, e.g. of a dry run
+        eprintln!("{}", msg);
+        Ok(())
+    }
+
+    fn on_process_started(&self, pid: Option<u32>) {
+        if let Some(pid) = pid {
+            info!("Game process started with PID {}", pid);
+        }
+    }
+
+    fn on_crash(&self, report: &CrashReport) {
+        // The explanation itself is already logged by the launcher
+        if let Some(suggestion) = &report.suggestion {
+            eprintln!("{}", suggestion);
+        }
+        for file in &report.files {
+            eprintln!("Crash report: {}", file.display());
+        }
+    }
+
+    fn hide_window(&self) {}
+
+    async fn show_download_page(&self, url: Url) -> Result<String> {
+        bail!(
+            "The download page {} cannot be opened from the command line. \
+            Launch the build once from the launcher window or use a premium account with skip advertisement enabled.",
+            url
+        )
+    }
+}
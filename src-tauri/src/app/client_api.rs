@@ -21,7 +21,7 @@ use std::collections::BTreeMap;
 
 use crate::auth::ClientAccount;
 use crate::minecraft::java::JavaDistribution;
-use crate::utils::get_maven_artifact_path;
+use crate::utils::{get_maven_artifact_path, Checksum};
 use crate::HTTP_CLIENT;
 use anyhow::{Error, Result};
 use chrono::{DateTime, NaiveDateTime, Utc};
@@ -41,16 +41,47 @@ pub const LAUNCHER_API: [&str; 3] = [
     "http://nossl.api.liquidbounce.net",
 ];
 
+/// Environment variable which overrides the API endpoints, e.g. to use a local mock server.
+/// Multiple endpoints are separated by commas and tried in order.
+pub const API_ENDPOINTS_ENV: &str = "LIQUIDLAUNCHER_API";
+
+/// Returns the API endpoints in the order they are tried.
+///
+/// The environment variable takes precedence over the custom endpoint of the launcher options,
+/// which takes precedence over [LAUNCHER_API].
+pub fn api_endpoints(custom_endpoint: &str) -> Vec<String> {
+    if let Ok(endpoints) = std::env::var(API_ENDPOINTS_ENV) {
+        let endpoints = endpoints
+            .split(',')
+            .map(|endpoint| endpoint.trim().trim_end_matches('/').to_string())
+            .filter(|endpoint| !endpoint.is_empty())
+            .collect::<Vec<_>>();
+
+        if !endpoints.is_empty() {
+            return endpoints;
+        }
+    }
+
+    if !custom_endpoint.is_empty() {
+        return vec![custom_endpoint.trim_end_matches('/').to_string()];
+    }
+
+    LAUNCHER_API.iter().map(|endpoint| endpoint.to_string()).collect()
+}
+
 pub const API_V1: &str = "api/v1";
 pub const API_V3: &str = "api/v3";
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Client {
     url: String,
     // To show a warning to the user when using a non-secure connection,
     // we need to pass this information to the frontend.
     is_secure: bool,
-    session_token: String
+    session_token: String,
+    // Set when no endpoint was reachable and builds are launched from the cache instead
+    #[serde(default)]
+    offline: bool,
 }
 
 impl Client {
@@ -58,7 +89,16 @@ impl Client {
         Self {
             url: host.to_string(),
             is_secure: host.starts_with("https://"),
-            session_token
+            session_token,
+            offline: false,
+        }
+    }
+
+    /// Creates a client that is not expected to reach the API, launches are done from the build cache
+    pub fn offline(host: &str, session_token: String) -> Self {
+        Self {
+            offline: true,
+            ..Self::new(host, session_token)
         }
     }
 
@@ -66,7 +106,7 @@ impl Client {
     /// and returns a [Client] instance with the endpoint set.
     ///
     /// Returns [String] as error with technical information if no API endpoint is reachable.
-    pub async fn lookup(session_token: String) -> Result<Self, String> {
+    pub async fn lookup(session_token: String, custom_endpoint: &str) -> Result<Self, String> {
         let span = debug_span!("api_lookup");
         let _guard = span.enter();
 
@@ -76,7 +116,7 @@ impl Client {
         let mut technical_information = String::new();
 
         info!(parent: &span, "Looking up available API endpoints");
-        for endpoint in LAUNCHER_API.iter() {
+        for endpoint in api_endpoints(custom_endpoint) {
             if !technical_information.is_empty() {
                 // Add a separator between each API endpoint
                 technical_information.push('\n');
@@ -92,7 +132,7 @@ impl Client {
             // this is as soon we get a SUCCESS response from the endpoint
             // e.g. 200 OK: LiquidBounce API written in Rust using Tokio Axum - @CCBlueX (Izuna).
             let is_success = HTTP_CLIENT
-                .get(*endpoint)
+                .get(&endpoint)
                 .send()
                 .await
                 .map_err(|err| {
@@ -130,7 +170,7 @@ impl Client {
 
             if is_success {
                 debug!(parent: &span, "API endpoint '{}' is available", endpoint);
-                return Ok(Self::new(endpoint, session_token));
+                return Ok(Self::new(&endpoint, session_token));
             }
         }
 
@@ -153,6 +193,10 @@ impl Client {
         &self.session_token
     }
 
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
     /// Request all blog posts
     pub async fn blog_posts(&self, page: u32) -> Result<PaginatedResponse<BlogPost>> {
         self.request_from_endpoint(API_V3, &format!("blog?page={}", page)).await
@@ -176,6 +220,12 @@ impl Client {
             .await
     }
 
+    /// Request launch manifest of specific build as it was sent by the API
+    pub async fn fetch_launch_manifest_json(&self, build_id: u32) -> Result<serde_json::Value> {
+        self.request_from_endpoint(API_V1, &format!("version/launch/{}", build_id))
+            .await
+    }
+
     /// Request list of downloadable mods for mc_version and used subsystem
     pub async fn fetch_mods(&self, mc_version: &str, subsystem: &str) -> Result<Vec<LoaderMod>> {
         self.request_from_endpoint(API_V1, &format!("version/mods/{}/{}", mc_version, subsystem))
@@ -340,6 +390,20 @@ pub struct LoaderMod {
     pub enabled: bool,
     pub name: String,
     pub source: ModSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl LoaderMod {
+    /// Returns the strongest checksum the mod JAR can be verified with
+    pub fn checksum(&self) -> Option<Checksum> {
+        self.sha256
+            .as_ref()
+            .map(Checksum::sha256)
+            .or_else(|| self.sha1.as_ref().map(Checksum::sha1))
+    }
 }
 
 ///
@@ -412,3 +476,92 @@ pub struct UserInformation {
     pub user_id: String,
     pub premium: bool,
 }
+
+/// The local stand-in for the API which is also used by the command line tests and the download tests
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "../../tests/mock_api/mod.rs"]
+pub(crate) mod mock_api;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::mock_api::MockApi;
+
+    fn client(api: &MockApi) -> Client {
+        Client::new(&api.url(), String::new())
+    }
+
+    fn client_account(expires_at: u64) -> ClientAccount {
+        serde_json::from_value(serde_json::json!({
+            "accessToken": "access",
+            "expiresAt": expires_at,
+            "refreshToken": "refresh",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn blog_posts_are_paginated() {
+        let api = MockApi::start();
+        let client = client(&api);
+
+        let first = client.blog_posts(1).await.unwrap();
+        assert_eq!(first.items.iter().map(|post| post.post_id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!((first.pagination.current, first.pagination.pages, first.pagination.items), (1, 2, 3));
+
+        let second = client.blog_posts(2).await.unwrap();
+        assert_eq!(second.items.iter().map(|post| post.post_id).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(second.pagination.current, 2);
+
+        assert_eq!(api.requests(), vec!["/api/v3/blog?page=1", "/api/v3/blog?page=2"]);
+    }
+
+    #[tokio::test]
+    async fn user_is_fetched_with_client_account() {
+        let api = MockApi::start();
+
+        let user = client(&api).fetch_user(&client_account(u64::MAX)).await.unwrap();
+
+        assert_eq!(user.nickname, "Tester");
+        assert_eq!(user.user_id, "1");
+        assert!(user.premium);
+        assert_eq!(api.requests(), vec!["/api/v3/oauth/user"]);
+    }
+
+    #[tokio::test]
+    async fn skip_file_is_resolved_with_client_account() {
+        let api = MockApi::start();
+
+        let resolve = client(&api)
+            .resolve_skip_file(&client_account(u64::MAX), "pid")
+            .await
+            .unwrap();
+
+        assert!(!resolve.error);
+        assert_eq!(resolve.target_pid.as_deref(), Some("d1r3ct"));
+        assert_eq!(api.requests(), vec!["/api/v3/file/resolve/pid"]);
+    }
+
+    #[tokio::test]
+    async fn expired_client_account_is_not_sent() {
+        let api = MockApi::start();
+        let client = client(&api);
+
+        assert!(client.fetch_user(&client_account(0)).await.is_err());
+        assert!(client.resolve_skip_file(&client_account(0), "pid").await.is_err());
+        assert!(api.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn unauthorized_requests_are_rejected() {
+        let api = MockApi::start();
+
+        let result = client(&api)
+            .request_from_endpoint::<UserInformation>(API_V3, "oauth/user")
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("401"));
+    }
+}
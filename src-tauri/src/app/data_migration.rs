@@ -0,0 +1,399 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use path_absolutize::Absolutize;
+use tracing::{error, info, warn};
+
+use crate::minecraft::progress::ProgressUpdate;
+use crate::utils::{disk_usage, is_path_in_use};
+
+/// Logs are always written to the default data directory and are still in use while migrating
+const LOGS_DIRECTORY: &str = "logs";
+
+/// Files of the config directory, which is the same folder as the default data directory on macOS
+const CONFIG_FILES: [&str; 2] = ["options.json", "instances.json"];
+
+/// Progress is only reported after this many bytes to avoid flooding the frontend with events
+const REPORT_STEP: u64 = 8 * 1024 * 1024;
+
+struct Progress<F: Fn(ProgressUpdate)> {
+    on_progress: F,
+    done: u64,
+    reported: u64,
+}
+
+impl<F: Fn(ProgressUpdate)> Progress<F> {
+    fn advance(&mut self, bytes: u64) {
+        self.done += bytes;
+        if self.done - self.reported >= REPORT_STEP {
+            self.reported = self.done;
+            (self.on_progress)(ProgressUpdate::SetProgress(self.done));
+        }
+    }
+
+    fn label<S: AsRef<str>>(&self, label: S) {
+        (self.on_progress)(ProgressUpdate::set_label(label));
+    }
+}
+
+///
+/// Moves the contents of the data directory `from` to `to`.
+///
+/// Every top-level entry is renamed if possible. When that fails, e.g. because the new location
+/// is on another device, the entry is copied, verified and only then removed from the old location.
+/// Symlinks (like the vanilla saves linked into the game directory) are recreated as symlinks.
+/// If any entry cannot be moved, all entries which were already moved are moved back.
+/// The logs and the files of `config_dir` stay where they are.
+///
+pub fn migrate_data_directory(
+    from: &Path,
+    to: &Path,
+    config_dir: &Path,
+    on_progress: impl Fn(ProgressUpdate),
+) -> Result<()> {
+    let from = from.absolutize()?.to_path_buf();
+    let to = to.absolutize()?.to_path_buf();
+    let config_dir = config_dir.absolutize()?.to_path_buf();
+
+    if from == to {
+        bail!("The data directory is already located at {}", to.display());
+    }
+    if to.starts_with(&from) || from.starts_with(&to) {
+        bail!("{} and {} must not contain each other", from.display(), to.display());
+    }
+    // The default data directory always contains the logs, which must not prevent migrating back
+    if to.is_dir()
+        && fs::read_dir(&to)?
+            .flatten()
+            .any(|entry| !is_kept(&entry.file_name(), &to, &config_dir))
+    {
+        bail!("{} is not empty", to.display());
+    }
+    if !from.is_dir() {
+        info!("No data directory at {}, nothing to migrate", from.display());
+        return Ok(());
+    }
+    // Also catches games which were not started by this process, e.g. from the command line
+    if is_path_in_use(&from) {
+        bail!("{} is in use by a running game", from.display());
+    }
+
+    fs::create_dir_all(&to)?;
+
+    let entries = fs::read_dir(&from)?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|entry| !is_kept(&entry.file_name(), &from, &config_dir))
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+
+    let total = entries.iter().map(|path| disk_usage(path)).sum::<u64>();
+    on_progress(ProgressUpdate::SetMax(total));
+    on_progress(ProgressUpdate::SetProgress(0));
+
+    let mut progress = Progress { on_progress, done: 0, reported: 0 };
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+
+    for source in entries {
+        let Some(name) = source.file_name() else { continue };
+        let target = to.join(name);
+        progress.label(format!("Moving {}...", name.to_string_lossy()));
+
+        if let Err(e) = move_entry(&source, &target, &mut progress) {
+            error!("Failed to move {}: {:?}", source.display(), e);
+            progress.label("Migration failed, restoring data directory...");
+            rollback(&moved);
+            return Err(e.context(format!("Failed to move {}", source.display())));
+        }
+        moved.push((source, target));
+    }
+
+    (progress.on_progress)(ProgressUpdate::SetProgress(total));
+    progress.label("Data directory migrated");
+    info!("Migrated data directory from {} to {}", from.display(), to.display());
+    Ok(())
+}
+
+/// Returns if the entry of the directory belongs to the launcher itself and is never migrated
+fn is_kept(name: &OsStr, directory: &Path, config_dir: &Path) -> bool {
+    name == LOGS_DIRECTORY || (directory == config_dir && CONFIG_FILES.iter().any(|file| name == *file))
+}
+
+fn move_entry<F: Fn(ProgressUpdate)>(source: &Path, target: &Path, progress: &mut Progress<F>) -> Result<()> {
+    let size = disk_usage(source);
+    if fs::rename(source, target).is_ok() {
+        progress.advance(size);
+        return Ok(());
+    }
+
+    // Most likely a cross-device move, copy and verify before anything is removed
+    if let Err(e) = copy_entry(source, target, progress).and_then(|_| verify(source, target)) {
+        if let Err(e) = remove(target) {
+            warn!("Failed to remove partial copy {}: {:?}", target.display(), e);
+        }
+        return Err(e);
+    }
+
+    // The copy is complete at this point, so leftovers are not worth a rollback
+    if let Err(e) = remove(source) {
+        warn!("Failed to remove {} after copying: {:?}", source.display(), e);
+    }
+    Ok(())
+}
+
+fn rollback(moved: &[(PathBuf, PathBuf)]) {
+    let mut progress = Progress { on_progress: |_: ProgressUpdate| {}, done: 0, reported: 0 };
+
+    for (source, target) in moved.iter().rev() {
+        if fs::rename(target, source).is_ok() {
+            continue;
+        }
+
+        let result = copy_entry(target, source, &mut progress)
+            .and_then(|_| verify(target, source))
+            .and_then(|_| remove(target));
+        if let Err(e) = result {
+            error!("Failed to restore {} from {}: {:?}", source.display(), target.display(), e);
+        }
+    }
+}
+
+fn copy_entry<F: Fn(ProgressUpdate)>(source: &Path, target: &Path, progress: &mut Progress<F>) -> Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+
+    if metadata.file_type().is_symlink() {
+        copy_symlink(source, target)?;
+    } else if metadata.is_dir() {
+        fs::create_dir_all(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &target.join(entry.file_name()), progress)?;
+        }
+    } else {
+        fs::copy(source, target)
+            .with_context(|| format!("Failed to copy {}", source.display()))?;
+        progress.advance(metadata.len());
+    }
+
+    Ok(())
+}
+
+fn copy_symlink(source: &Path, target: &Path) -> Result<()> {
+    let link = fs::read_link(source)?;
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&link, target)?;
+
+    #[cfg(windows)]
+    {
+        if source.is_dir() {
+            std::os::windows::fs::symlink_dir(&link, target)?;
+        } else {
+            std::os::windows::fs::symlink_file(&link, target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Compares the copy against its source by structure, file sizes and link targets
+fn verify(source: &Path, target: &Path) -> Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    let copied = fs::symlink_metadata(target)
+        .with_context(|| format!("{} is missing", target.display()))?;
+
+    if metadata.file_type().is_symlink() {
+        if !copied.file_type().is_symlink() || fs::read_link(source)? != fs::read_link(target)? {
+            bail!("Link {} was not preserved", target.display());
+        }
+    } else if metadata.is_dir() {
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            verify(&entry.path(), &target.join(entry.file_name()))?;
+        }
+    } else if metadata.len() != copied.len() {
+        bail!("{} has {} bytes instead of {}", target.display(), copied.len(), metadata.len());
+    }
+
+    Ok(())
+}
+
+fn remove(path: &Path) -> Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(()),
+    };
+
+    if metadata.file_type().is_symlink() {
+        // Directory links on Windows have to be removed as directories
+        fs::remove_file(path).or_else(|_| fs::remove_dir(path))?;
+    } else if metadata.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Creates an empty directory which is unique to this test run
+    fn temp_directory() -> PathBuf {
+        static RUN: AtomicU32 = AtomicU32::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "liquidlauncher-migration-{}-{}",
+            std::process::id(),
+            RUN.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn silent() -> Progress<impl Fn(ProgressUpdate)> {
+        Progress { on_progress: |_: ProgressUpdate| {}, done: 0, reported: 0 }
+    }
+
+    /// Creates a data directory with nested files and a log which must stay in place
+    fn populate(data: &Path) {
+        fs::create_dir_all(data.join("versions/1.21.4")).unwrap();
+        fs::write(data.join("versions/1.21.4/1.21.4.json"), "{}").unwrap();
+        fs::write(data.join("sessions.json"), "[]").unwrap();
+        fs::create_dir_all(data.join(LOGS_DIRECTORY)).unwrap();
+        fs::write(data.join("logs/launcher.log"), "log").unwrap();
+    }
+
+    #[test]
+    fn migrates_into_directory_with_logs_only() {
+        let root = temp_directory();
+        let (from, to) = (root.join("custom"), root.join("default"));
+        populate(&from);
+        fs::create_dir_all(to.join(LOGS_DIRECTORY)).unwrap();
+        fs::write(to.join("logs/launcher.log"), "log").unwrap();
+
+        migrate_data_directory(&from, &to, &root.join("config"), |_| {}).unwrap();
+
+        assert_eq!(fs::read_to_string(to.join("versions/1.21.4/1.21.4.json")).unwrap(), "{}");
+        assert_eq!(fs::read_to_string(to.join("sessions.json")).unwrap(), "[]");
+        assert!(!from.join("versions").exists());
+        assert!(!from.join("sessions.json").exists());
+        assert!(from.join("logs/launcher.log").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn keeps_config_files_in_shared_directory() {
+        let root = temp_directory();
+        let (default, custom) = (root.join("default"), root.join("custom"));
+        populate(&default);
+        fs::write(default.join("options.json"), "{}").unwrap();
+        fs::write(default.join("instances.json"), "{}").unwrap();
+
+        // The config directory is the default data directory, like on macOS
+        migrate_data_directory(&default, &custom, &default, |_| {}).unwrap();
+
+        assert!(custom.join("versions/1.21.4/1.21.4.json").exists());
+        assert!(!custom.join("options.json").exists());
+        assert!(default.join("options.json").exists());
+        assert!(default.join("instances.json").exists());
+
+        migrate_data_directory(&custom, &default, &default, |_| {}).unwrap();
+
+        assert!(default.join("versions/1.21.4/1.21.4.json").exists());
+        assert!(default.join("sessions.json").exists());
+        assert_eq!(fs::read_dir(&custom).unwrap().count(), 0);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn refuses_non_empty_target() {
+        let root = temp_directory();
+        let (from, to) = (root.join("custom"), root.join("default"));
+        populate(&from);
+        fs::create_dir_all(&to).unwrap();
+        fs::write(to.join("options.json"), "{}").unwrap();
+
+        assert!(migrate_data_directory(&from, &to, &root.join("config"), |_| {}).is_err());
+        assert!(from.join("versions/1.21.4/1.21.4.json").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn verifies_copied_entries() {
+        let root = temp_directory();
+        let (source, target) = (root.join("source"), root.join("target"));
+        populate(&source);
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("versions", source.join("link")).unwrap();
+
+        let mut progress = silent();
+        copy_entry(&source, &target, &mut progress).unwrap();
+        verify(&source, &target).unwrap();
+        assert_eq!(progress.done, 7);
+        #[cfg(unix)]
+        assert_eq!(fs::read_link(target.join("link")).unwrap(), PathBuf::from("versions"));
+
+        // An incomplete copy must never be accepted
+        fs::write(target.join("versions/1.21.4/1.21.4.json"), "").unwrap();
+        assert!(verify(&source, &target).is_err());
+        fs::remove_file(target.join("sessions.json")).unwrap();
+        assert!(verify(&source, &target).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rolls_back_copied_entries() {
+        let root = temp_directory();
+        let (from, to) = (root.join("custom"), root.join("default"));
+        populate(&from);
+        fs::create_dir_all(&to).unwrap();
+
+        // Moves the entries the way a cross-device migration does, then restores them
+        let mut moved = Vec::new();
+        for name in ["versions", "sessions.json"] {
+            let (source, target) = (from.join(name), to.join(name));
+            copy_entry(&source, &target, &mut silent()).unwrap();
+            verify(&source, &target).unwrap();
+            remove(&source).unwrap();
+            moved.push((source, target));
+        }
+        rollback(&moved);
+
+        assert_eq!(fs::read_to_string(from.join("versions/1.21.4/1.21.4.json")).unwrap(), "{}");
+        assert_eq!(fs::read_to_string(from.join("sessions.json")).unwrap(), "[]");
+        assert!(!to.join("versions").exists());
+        assert!(!to.join("sessions.json").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
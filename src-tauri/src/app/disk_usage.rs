@@ -0,0 +1,192 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use crate::app::instance::Instances;
+use crate::utils::disk_usage;
+use crate::LAUNCHER_DIRECTORY;
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum UsageCategory {
+    Runtime,
+    Version,
+    Libraries,
+    Assets,
+    ModCache,
+    CustomMods,
+    GameDirectory,
+    Instance,
+    Cache,
+    Logs,
+}
+
+impl UsageCategory {
+    /// Checks if the launcher downloads the entries again when they are needed,
+    /// everything else holds worlds, configs, user mods or logs which would be lost
+    pub fn is_removable(&self) -> bool {
+        matches!(
+            self,
+            UsageCategory::Runtime
+                | UsageCategory::Version
+                | UsageCategory::Libraries
+                | UsageCategory::Assets
+                | UsageCategory::ModCache
+                | UsageCategory::Cache
+        )
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEntry {
+    pub category: UsageCategory,
+    pub name: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub removable: bool,
+}
+
+///
+/// Size of everything the launcher stores, largest entries first
+///
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsage {
+    pub data_directory: PathBuf,
+    pub total_bytes: u64,
+    pub entries: Vec<UsageEntry>,
+}
+
+/// Lists the folders which are measured, runtimes, versions, game directories and instances one by one
+fn locations(data: &Path, instances: &Instances) -> Vec<(UsageCategory, String, PathBuf)> {
+    let mut locations = vec![];
+
+    for (category, folder) in [
+        (UsageCategory::Runtime, "runtimes"),
+        (UsageCategory::Version, "versions"),
+        (UsageCategory::GameDirectory, "gameDir"),
+        (UsageCategory::Instance, "instances"),
+    ] {
+        let Ok(entries) = std::fs::read_dir(data.join(folder)) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let name = match category {
+                UsageCategory::Instance => instances
+                    .get(&file_name)
+                    .map(|instance| instance.name.clone())
+                    .unwrap_or(file_name),
+                _ => file_name,
+            };
+            locations.push((category, name, entry.path()));
+        }
+    }
+
+    for (category, folder) in [
+        (UsageCategory::Libraries, "libraries"),
+        (UsageCategory::Assets, "assets"),
+        (UsageCategory::ModCache, "mod_cache"),
+        (UsageCategory::CustomMods, "custom_mods"),
+        (UsageCategory::Cache, "cache"),
+        (UsageCategory::Logs, "session_logs"),
+    ] {
+        locations.push((category, folder.to_string(), data.join(folder)));
+    }
+
+    // Logs are always written to the default data directory
+    locations.push((
+        UsageCategory::Logs,
+        "logs".to_string(),
+        LAUNCHER_DIRECTORY.data_dir().join("logs"),
+    ));
+
+    locations.retain(|(_, _, path)| path.exists());
+    locations
+}
+
+/// Measures the data directory, which walks every file in it
+pub(crate) fn measure(data: &Path, instances: &Instances) -> DiskUsage {
+    let mut entries = locations(data, instances)
+        .into_iter()
+        .map(|(category, name, path)| UsageEntry {
+            category,
+            name,
+            bytes: disk_usage(&path),
+            removable: category.is_removable(),
+            path,
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+    DiskUsage {
+        data_directory: data.to_path_buf(),
+        total_bytes: entries.iter().map(|entry| entry.bytes).sum(),
+        entries,
+    }
+}
+
+/// Deletes one of the removable entries reported by [measure], any other path is refused
+pub(crate) fn remove_entry(data: &Path, instances: &Instances, path: &Path) -> Result<()> {
+    let Some((category, _, _)) = locations(data, instances)
+        .into_iter()
+        .find(|(_, _, location)| location == path)
+    else {
+        bail!("{} is not part of the launcher data", path.display());
+    };
+    if !category.is_removable() {
+        bail!("{} can't be downloaded again and is not removed", path.display());
+    }
+
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+    .with_context(|| format!("Failed to remove {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_only_downloadable_entries() {
+        let data = std::env::temp_dir().join(format!("liquidlauncher-disk-usage-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&data);
+        std::fs::create_dir_all(data.join("gameDir/nextgen/saves")).unwrap();
+        std::fs::create_dir_all(data.join("versions/1.21.4")).unwrap();
+        let instances = Instances::default();
+
+        assert!(remove_entry(&data, &instances, &data.join("gameDir/nextgen")).is_err());
+        assert!(data.join("gameDir/nextgen/saves").exists());
+        assert!(remove_entry(&data, &instances, &data.join("gameDir/nextgen/saves")).is_err());
+
+        remove_entry(&data, &instances, &data.join("versions/1.21.4")).unwrap();
+        assert!(!data.join("versions/1.21.4").exists());
+
+        std::fs::remove_dir_all(&data).unwrap();
+    }
+}
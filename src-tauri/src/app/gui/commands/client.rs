@@ -17,41 +17,72 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
 use backon::{ExponentialBuilder, Retryable};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::{
     sync::{Arc, Mutex},
     thread,
 };
-use minecraft_auth::java::JavaAuthManager;
-use minecraft_auth::msa::constants::JAVA_TITLE_ID;
-use tauri::{Emitter, Window};
+use tauri::{Emitter, Url, Window};
 use tokio::fs;
-use tracing::{error, info, warn};
-use uuid::Uuid;
+use tracing::{info, warn};
 
-use crate::app::client_api::{BlogPost, Build, Changelog, Client, PaginatedResponse};
+use crate::app::client_api::{BlogPost, Build, Changelog, Client, LaunchManifest, PaginatedResponse};
 use crate::app::client_api::{LoaderMod, ModSource};
-use crate::app::options::Options;
-use crate::{app::gui::{AppState, RunnerInstance, ShareableWindow}, minecraft::{
-    auth::MinecraftAccount,
-    launcher::{LauncherData, StartParameter},
+use crate::app::options::{BranchOptions, Options};
+use crate::app::webview::show_webview;
+use crate::error::LauncherError;
+use crate::{app::gui::{runner::{InstanceFrontend, RunnerStatus}, AppState, ShareableWindow}, minecraft::{
+    cache::BuildCache,
+    crash::CrashReport,
+    java::output::{LogLevel, OutputLine},
+    launcher::{plan::LaunchPlan, repair::RepairReport, LauncherData, LauncherFrontend, StartParameter},
     prelauncher,
     progress::ProgressUpdate,
-}, HTTP_CLIENT, LAUNCHER_DIRECTORY};
+}};
 
 #[tauri::command]
-pub(crate) async fn request_builds(client: Client, release: bool) -> Result<Vec<Build>, String> {
-    let builds = (|| async { client.builds(release).await })
-        .retry(ExponentialBuilder::default())
-        .notify(|err, dur| {
-            warn!("Failed to request builds. Retrying in {:?}. Error: {}", dur, err);
-        })
-        .await
-        .map_err(|e| format!("unable to request builds: {:?}", e))?;
+pub(crate) async fn request_builds(client: Client, options: Options, release: bool) -> Result<Vec<Build>, String> {
+    load_builds(&client, &options, release).await
+}
+
+/// Requests the builds from the API, falling back to the cached builds when offline
+pub(crate) async fn load_builds(client: &Client, options: &Options, release: bool) -> Result<Vec<Build>, String> {
+    let data_directory = options.start_options.data_directory();
+
+    let builds = if options.launcher_options.offline_mode || client.is_offline() {
+        Err(anyhow!("offline mode is enabled"))
+    } else {
+        (|| async { client.builds(release).await })
+            .retry(ExponentialBuilder::default())
+            .notify(|err, dur| {
+                warn!("Failed to request builds. Retrying in {:?}. Error: {}", dur, err);
+            })
+            .await
+    };
 
-    Ok(builds)
+    match builds {
+        Ok(builds) => Ok(builds),
+        Err(e) => {
+            // Fall back to the builds which can be launched offline
+            let cached_builds = BuildCache::cached_builds(&data_directory)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|build| build.release || !release)
+                .collect::<Vec<_>>();
+
+            if cached_builds.is_empty() {
+                return Err(format!("unable to request builds: {:?}", e));
+            }
+
+            warn!("Unable to request builds, using cached builds: {:?}", e);
+            Ok(cached_builds)
+        }
+    }
 }
 
 #[tauri::command]
@@ -82,6 +113,10 @@ pub(crate) async fn request_mods(
     mc_version: &str,
     subsystem: &str,
 ) -> Result<Vec<LoaderMod>, String> {
+    if client.is_offline() {
+        return Err("unable to request mods: the API is not reachable".to_string());
+    }
+
     let mods = (|| async { client.fetch_mods(&mc_version, &subsystem).await })
         .retry(ExponentialBuilder::default())
         .notify(|err, dur| {
@@ -93,15 +128,21 @@ pub(crate) async fn request_mods(
     Ok(mods)
 }
 
+/// Returns the folder the custom mods of a branch and Minecraft version are installed to
+fn custom_mods_directory(data: &Path, branch: &str, mc_version: &str) -> PathBuf {
+    data.join("custom_mods").join(format!("{}-{}", branch, mc_version))
+}
+
 #[tauri::command]
 pub(crate) async fn get_custom_mods(
+    options: Options,
     branch: &str,
     mc_version: &str,
 ) -> Result<Vec<LoaderMod>, String> {
-    let data = LAUNCHER_DIRECTORY.data_dir();
-    let mod_cache_path = data
-        .join("custom_mods")
-        .join(format!("{}-{}", branch, mc_version));
+    load_custom_mods(&custom_mods_directory(&options.start_options.data_directory(), branch, mc_version)).await
+}
+
+async fn load_custom_mods(mod_cache_path: &Path) -> Result<Vec<LoaderMod>, String> {
 
     if !mod_cache_path.exists() {
         return Ok(vec![]);
@@ -132,6 +173,8 @@ pub(crate) async fn get_custom_mods(
                 enabled: true,
                 name: file_name_without_extension,
                 source: ModSource::Local { file_name },
+                sha1: None,
+                sha256: None,
             });
         }
     }
@@ -141,14 +184,12 @@ pub(crate) async fn get_custom_mods(
 
 #[tauri::command]
 pub(crate) async fn install_custom_mod(
+    options: Options,
     branch: &str,
     mc_version: &str,
     path: PathBuf,
 ) -> Result<(), String> {
-    let data = LAUNCHER_DIRECTORY.data_dir();
-    let mod_cache_path = data
-        .join("custom_mods")
-        .join(format!("{}-{}", branch, mc_version));
+    let mod_cache_path = custom_mods_directory(&options.start_options.data_directory(), branch, mc_version);
 
     if !mod_cache_path.exists() {
         fs::create_dir_all(&mod_cache_path).await.unwrap();
@@ -168,14 +209,12 @@ pub(crate) async fn install_custom_mod(
 
 #[tauri::command]
 pub(crate) async fn delete_custom_mod(
+    options: Options,
     branch: &str,
     mc_version: &str,
     mod_name: &str,
 ) -> Result<(), String> {
-    let data = LAUNCHER_DIRECTORY.data_dir();
-    let mod_cache_path = data
-        .join("custom_mods")
-        .join(format!("{}-{}", branch, mc_version));
+    let mod_cache_path = custom_mods_directory(&options.start_options.data_directory(), branch, mc_version);
 
     if !mod_cache_path.exists() {
         return Ok(());
@@ -192,57 +231,47 @@ pub(crate) async fn delete_custom_mod(
     Ok(())
 }
 
-fn handle_stdout(window: &ShareableWindow, data: &[u8]) -> anyhow::Result<()> {
-    let data = String::from_utf8(data.to_vec())?;
-    if data.is_empty() {
-        return Ok(()); // ignore empty lines
+impl LauncherFrontend for ShareableWindow {
+    fn on_output(&self, line: &OutputLine) -> anyhow::Result<()> {
+        line.log("");
+        self.lock()
+            .map_err(|_| anyhow!("Window lock is poisoned"))?
+            .emit("process-output", line)?;
+        Ok(())
     }
 
-    info!("{}", data.strip_suffix("\n").unwrap_or(&data));
-    window
-        .lock()
-        .map_err(|_| anyhow!("Window lock is poisoned"))?
-        .emit("process-output", data)?;
-    Ok(())
-}
+    fn on_progress(&self, progress_update: ProgressUpdate) -> anyhow::Result<()> {
+        self.lock()
+            .map_err(|_| anyhow!("Window lock is poisoned"))?
+            .emit("progress-update", &progress_update)?;
 
-fn handle_stderr(window: &ShareableWindow, data: &[u8]) -> anyhow::Result<()> {
-    let data = String::from_utf8(data.to_vec())?;
-    if data.is_empty() {
-        return Ok(()); // ignore empty lines
+        // Check if progress update is label update
+        if let ProgressUpdate::SetLabel(label) = progress_update {
+            self.on_log(&label)?;
+        }
+        Ok(())
     }
 
-    error!("{}", data.strip_suffix("\n").unwrap_or(&data));
-    window
-        .lock()
-        .map_err(|_| anyhow!("Window lock is poisoned"))?
-        .emit("process-output", data)?;
-    Ok(())
-}
-
-fn handle_progress(
-    window: &ShareableWindow,
-    progress_update: ProgressUpdate,
-) -> anyhow::Result<()> {
-    window
-        .lock()
-        .map_err(|_| anyhow!("Window lock is poisoned"))?
-        .emit("progress-update", &progress_update)?;
+    fn on_log(&self, msg: &str) -> anyhow::Result<()> {
+        info!("{}", msg);
 
-    // Check if progress update is label update
-    if let ProgressUpdate::SetLabel(label) = progress_update {
-        handle_log(window, &label)?;
+        if let Ok(k) = self.lock() {
+            let _ = k.emit("process-output", OutputLine::launcher(LogLevel::Info, msg));
+        }
+        Ok(())
     }
-    Ok(())
-}
 
-fn handle_log(window: &ShareableWindow, msg: &str) -> anyhow::Result<()> {
-    info!("{}", msg);
+    fn on_process_started(&self, _pid: Option<u32>) {}
 
-    if let Ok(k) = window.lock() {
-        let _ = k.emit("process-output", msg);
+    fn on_crash(&self, _report: &CrashReport) {}
+
+    fn hide_window(&self) {
+        self.lock().unwrap().hide().unwrap();
+    }
+
+    fn show_download_page(&self, url: Url) -> impl Future<Output = anyhow::Result<String>> {
+        show_webview(url, self)
     }
-    Ok(())
 }
 
 #[tauri::command]
@@ -253,88 +282,120 @@ pub(crate) async fn run_client(
     mods: Vec<LoaderMod>,
     window: Window,
     app_state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
-    // A shared mutex for the window object.
-    let shareable_window: ShareableWindow = Arc::new(Mutex::new(window));
+) -> Result<u32, String> {
+    let parameters = StartParameter::from_options(client, options)
+        .await
+        .map_err(|e| format!("{:#}", e))?;
 
-    let minecraft_account = options
-        .start_options
-        .minecraft_account
-        .ok_or("no account selected")?;
-    let (account_name, uuid, token, user_type) = match minecraft_account {
-        MinecraftAccount::MsaAccount { state, name, id } => {
-            let manager = JavaAuthManager::from_json(HTTP_CLIENT.clone(), &state)
-                .map_err(|e| format!("unable to load account: {}", e))?;
-            let token = manager
-                .minecraft_token()
-                .await
-                .map_err(|e| format!("unable to refresh account: {}", e))?;
-            (name, id.to_string(), token.access_token, "msa".to_string())
-        }
-        MinecraftAccount::OfflineAccount { name, id, .. } => {
-            (name, id.to_string(), "-".to_string(), "legacy".to_string())
-        }
-    };
+    info!("Loading launch manifest...");
+    let launch_manifest = prelauncher::load_launch_manifest(build_id, &parameters).await.map_err(|e| {
+        format!(
+            "failed to fetch launch manifest of build {}: {:?}",
+            build_id, e
+        )
+    })?;
 
-    let client_account = options.premium_options.account;
-    let skip_advertisement = options.premium_options.skip_advertisement
-        && client_account
-        .as_ref()
-        .is_some_and(|x| x.get_user_information().is_some_and(|u| u.premium));
+    launch_client(launch_manifest, parameters, mods, window, &app_state)
+}
 
-    // Random XUID
-    let xuid = Uuid::new_v4().to_string();
+/// Resolves what launching the build would do, without starting the client
+#[tauri::command]
+pub(crate) async fn dry_run(
+    client: Client,
+    build_id: u32,
+    options: Options,
+    mods: Vec<LoaderMod>,
+    window: Window,
+) -> Result<LaunchPlan, String> {
+    let parameters = StartParameter::from_options(client, options)
+        .await
+        .map_err(|e| format!("{:#}", e))?;
 
-    let runner_instance = &app_state.runner_instance;
+    let launch_manifest = prelauncher::load_launch_manifest(build_id, &parameters).await.map_err(|e| {
+        format!(
+            "failed to fetch launch manifest of build {}: {:?}",
+            build_id, e
+        )
+    })?;
 
-    if runner_instance
-        .lock()
-        .map_err(|e| format!("unable to lock runner instance: {:?}", e))?
-        .is_some()
-    {
-        return Err("client is already running".to_string());
-    }
+    let shareable_window: ShareableWindow = Arc::new(Mutex::new(window));
 
-    info!("Loading launch manifest...");
-    let launch_manifest = client.fetch_launch_manifest(build_id).await.map_err(|e| {
+    // Nothing is started, so there is nothing to terminate
+    let (_, terminator_rx) = tokio::sync::oneshot::channel();
+    let launcher_data = LauncherData {
+        data: Box::new(shareable_window),
+        terminator: terminator_rx,
+    };
+
+    prelauncher::dry_run(launch_manifest, &parameters, mods, &launcher_data)
+        .await
+        .map_err(|e| format!("unable to resolve launch plan: {:?}", e))
+}
+
+/// Verifies the installation of the build, missing or corrupted files are downloaded again if [repair] is set
+#[tauri::command]
+pub(crate) async fn repair_installation(
+    client: Client,
+    build_id: u32,
+    options: Options,
+    mods: Vec<LoaderMod>,
+    repair: bool,
+    window: Window,
+) -> Result<RepairReport, String> {
+    let parameters = StartParameter::from_options(client, options)
+        .await
+        .map_err(|e| format!("{:#}", e))?;
+
+    let launch_manifest = prelauncher::load_launch_manifest(build_id, &parameters).await.map_err(|e| {
         format!(
             "failed to fetch launch manifest of build {}: {:?}",
             build_id, e
         )
     })?;
 
+    let shareable_window: ShareableWindow = Arc::new(Mutex::new(window));
+
+    let (_, terminator_rx) = tokio::sync::oneshot::channel();
+    let launcher_data = LauncherData {
+        data: Box::new(shareable_window),
+        terminator: terminator_rx,
+    };
+
+    prelauncher::repair(launch_manifest, &parameters, mods, &launcher_data, repair)
+        .await
+        .map_err(|e| format!("unable to verify installation: {:?}", e))
+}
+
+/// Starts the client on a separate thread and returns the id of the instance,
+/// the window is notified when the client exits
+pub(crate) fn launch_client(
+    launch_manifest: LaunchManifest,
+    parameters: StartParameter,
+    mods: Vec<LoaderMod>,
+    window: Window,
+    app_state: &AppState,
+) -> Result<u32, String> {
+    // A shared mutex for the window object.
+    let shareable_window: ShareableWindow = Arc::new(Mutex::new(window));
+
     let (terminator_tx, terminator_rx) = tokio::sync::oneshot::channel();
 
-    *runner_instance
+    let name = format!(
+        "{} ({})",
+        launch_manifest.build.branch, launch_manifest.build.commit_id
+    );
+    let id = app_state
+        .runners
         .lock()
-        .map_err(|e| format!("unable to lock runner instance: {:?}", e))? = Some(RunnerInstance {
-        terminator: terminator_tx,
-    });
+        .map_err(|e| format!("unable to lock runners: {:?}", e))?
+        .register(name, terminator_tx);
 
-    let copy_of_runner_instance = runner_instance.clone();
-
-    let parameters = StartParameter {
-        java_distribution: options.start_options.java_distribution,
-        jvm_args: options.start_options.jvm_args.unwrap_or_else(|| vec![]),
-        memory: options.start_options.memory,
-        custom_data_path: if !options.start_options.custom_data_path.is_empty() {
-            Some(options.start_options.custom_data_path)
-        } else {
-            None
-        },
-        auth_player_name: account_name,
-        auth_uuid: uuid,
-        auth_access_token: token,
-        auth_xuid: xuid,
-        clientid: JAVA_TITLE_ID.to_string(),
-        user_type,
-        keep_launcher_open: options.launcher_options.keep_launcher_open,
-        concurrent_downloads: options.launcher_options.concurrent_downloads,
-        client,
-        client_account,
-        skip_advertisement,
-        vanilla_integration: options.start_options.installation,
+    let frontend = InstanceFrontend {
+        id,
+        window: shareable_window.clone(),
+        runners: app_state.runners.clone(),
     };
+    frontend.instances_changed();
 
     thread::spawn(move || {
         tokio::runtime::Builder::new_current_thread()
@@ -345,56 +406,143 @@ pub(crate) async fn run_client(
                 let keep_launcher_open = parameters.keep_launcher_open;
 
                 let launcher_data = LauncherData {
-                    on_stdout: handle_stdout,
-                    on_stderr: handle_stderr,
-                    on_progress: handle_progress,
-                    on_log: handle_log,
-                    hide_window: |w| w.lock().unwrap().hide().unwrap(),
-                    data: Box::new(shareable_window.clone()),
+                    data: Box::new(frontend.clone()),
                     terminator: terminator_rx,
                 };
 
-                if let Err(e) =
-                    prelauncher::launch(launch_manifest, parameters, mods, launcher_data).await
-                {
-                    if !keep_launcher_open {
-                        shareable_window.lock().unwrap().show().unwrap();
+                let status = match prelauncher::launch(launch_manifest, parameters, mods, launcher_data).await {
+                    Ok(()) => RunnerStatus::Exited,
+                    Err(e) => {
+                        if !keep_launcher_open {
+                            shareable_window.lock().unwrap().show().unwrap();
+                        }
+
+                        let message = format!("An error occured:\n\n{:?}", e);
+                        shareable_window
+                            .lock()
+                            .unwrap()
+                            .emit("client-error", id)
+                            .unwrap();
+
+                        // Offer to switch to the automatic distribution instead
+                        if let Some(error) = e
+                            .chain()
+                            .find_map(|cause| cause.downcast_ref::<LauncherError>())
+                            .filter(|error| error.is_custom_java_error())
+                        {
+                            shareable_window
+                                .lock()
+                                .unwrap()
+                                .emit("custom-java-error", error.to_string())
+                                .unwrap();
+                        }
+                        frontend.on_output(&OutputLine::launcher(LogLevel::Error, &message)).unwrap();
+                        RunnerStatus::Failed
                     }
-
-                    let message = format!("An error occured:\n\n{:?}", e);
-                    shareable_window
-                        .lock()
-                        .unwrap()
-                        .emit("client-error", ())
-                        .unwrap();
-                    handle_stderr(&shareable_window, message.as_bytes()).unwrap();
                 };
 
-                *copy_of_runner_instance
-                    .lock()
-                    .map_err(|e| format!("unable to lock runner instance: {:?}", e))
-                    .unwrap() = None;
+                frontend.finish(status);
                 shareable_window
                     .lock()
                     .unwrap()
-                    .emit("client-exited", ())
-                    .unwrap()
+                    .emit("client-exited", id)
+                    .unwrap();
+
+                // Close the launcher with the last game only, exiting earlier would orphan the other instances
+                if status == RunnerStatus::Exited
+                    && !keep_launcher_open
+                    && !frontend.runners.lock().unwrap().has_active()
+                {
+                    std::process::exit(0);
+                }
             });
     });
 
-    Ok(())
+    Ok(id)
 }
 
+/// Terminates the given instance, or every running instance if none is given
 #[tauri::command]
-pub(crate) async fn terminate(app_state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut lck = app_state
-        .runner_instance
+pub(crate) async fn terminate(
+    instance_id: Option<u32>,
+    app_state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let mut runners = app_state
+        .runners
         .lock()
-        .map_err(|e| format!("unable to lock runner instance: {:?}", e))?;
+        .map_err(|e| format!("unable to lock runners: {:?}", e))?;
 
-    if let Some(inst) = lck.take() {
-        info!("Sending sigterm");
-        inst.terminator.send(()).unwrap();
+    info!("Sending sigterm");
+    match instance_id {
+        Some(id) => {
+            runners.terminate(id);
+        }
+        None => runners.terminate_all(),
     }
     Ok(())
 }
+
+/// Uses the given build, otherwise the build selected in the launcher or the latest one.
+pub(crate) async fn resolve_build_id(
+    client: &Client,
+    options: &Options,
+    build_id: Option<u32>,
+) -> anyhow::Result<u32> {
+    if let Some(build_id) = build_id {
+        return Ok(build_id);
+    }
+
+    if let Ok(build_id) = u32::try_from(options.version_options.build_id) {
+        return Ok(build_id);
+    }
+
+    let release = !options.launcher_options.show_nightly_builds;
+    let builds = if options.launcher_options.offline_mode || client.is_offline() {
+        BuildCache::cached_builds(&options.start_options.data_directory())
+            .await?
+            .into_iter()
+            .filter(|build| build.release || !release)
+            .collect()
+    } else {
+        load_builds(client, options, release)
+            .await
+            .map_err(|e| anyhow!(e))?
+    };
+
+    builds
+        .first()
+        .map(|build| build.build_id)
+        .context("no builds available")
+}
+
+/// Collects the recommended mods and the custom mods installed in the data directory,
+/// and applies the mod states stored in the options.
+pub(crate) async fn collect_mods(
+    client: &Client,
+    data: &Path,
+    branch_options: &HashMap<String, BranchOptions>,
+    build: &Build,
+) -> anyhow::Result<Vec<LoaderMod>> {
+    let mut mods = request_mods(client.clone(), &build.mc_version, &build.subsystem)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    let mut custom_mods = load_custom_mods(&custom_mods_directory(data, &build.branch, &build.mc_version))
+        .await
+        .map_err(|e| anyhow!(e))?;
+
+    if let Some(branch_options) = branch_options.get(&build.branch) {
+        for current_mod in mods.iter_mut() {
+            if let Some(enabled) = branch_options.mod_states.get(&current_mod.name) {
+                current_mod.enabled = *enabled;
+            }
+        }
+        for current_mod in custom_mods.iter_mut() {
+            if let Some(enabled) = branch_options.custom_mod_states.get(&current_mod.name) {
+                current_mod.enabled = *enabled;
+            }
+        }
+    }
+
+    mods.append(&mut custom_mods);
+    Ok(mods)
+}
@@ -17,8 +17,19 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::path::PathBuf;
+
+use tauri::{Emitter, Window};
+use tracing::error;
+
 use crate::{
+    app::data_migration,
+    app::disk_usage::{self, DiskUsage},
+    app::instance::Instances,
+    app::gui::AppState,
     app::options::Options,
+    minecraft::launcher::gc::{self, GarbageReport},
+    minecraft::session::{SessionHistory, SessionRecord},
     LAUNCHER_DIRECTORY
 };
 
@@ -41,17 +52,12 @@ pub(crate) async fn store_options(options: Options) -> Result<(), String> {
 
 #[tauri::command]
 pub(crate) async fn clear_data(options: Options) -> Result<(), String> {
-    let data_directory = if !options.start_options.custom_data_path.is_empty() {
-        Some(options.start_options.custom_data_path)
-    } else {
-        None
-    }
-        .map(|x| x.into())
-        .unwrap_or_else(|| LAUNCHER_DIRECTORY.data_dir().to_path_buf());
+    let data_directory = options.start_options.data_directory();
 
     [
         "assets",
         "gameDir",
+        "instances",
         "libraries",
         "mod_cache",
         "natives",
@@ -67,6 +73,137 @@ pub(crate) async fn clear_data(options: Options) -> Result<(), String> {
     Ok(())
 }
 
+/// Deletes libraries, assets, versions and runtimes which none of the launched builds use
+#[tauri::command]
+pub(crate) async fn collect_garbage(
+    options: Options,
+    dry_run: bool,
+    app_state: tauri::State<'_, AppState>,
+) -> Result<GarbageReport, String> {
+    if !dry_run
+        && app_state
+            .runners
+            .lock()
+            .map_err(|e| format!("unable to lock runners: {:?}", e))?
+            .has_active()
+    {
+        return Err("unable to collect garbage while the client is running".to_string());
+    }
+
+    let data_directory = options.start_options.data_directory();
+    let instances = Instances::load(LAUNCHER_DIRECTORY.config_dir())
+        .await
+        .map_err(|e| format!("unable to load instances: {:?}", e))?;
+
+    gc::collect_garbage(&data_directory, &instances.java_distributions(&options), dry_run)
+        .await
+        .map_err(|e| format!("unable to collect garbage: {:?}", e))
+}
+
+/// Reports the size of the runtimes, versions, game directories and other parts of the data directory
+#[tauri::command]
+pub(crate) async fn get_disk_usage(options: Options) -> Result<DiskUsage, String> {
+    let data_directory = options.start_options.data_directory();
+    let instances = Instances::load(LAUNCHER_DIRECTORY.config_dir())
+        .await
+        .unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || disk_usage::measure(&data_directory, &instances))
+        .await
+        .map_err(|e| format!("unable to measure disk usage: {:?}", e))
+}
+
+/// Removes a single entry of [get_disk_usage]
+#[tauri::command]
+pub(crate) async fn remove_disk_usage_entry(
+    options: Options,
+    path: PathBuf,
+    app_state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if app_state
+        .runners
+        .lock()
+        .map_err(|e| format!("unable to lock runners: {:?}", e))?
+        .has_active()
+    {
+        return Err("unable to remove data while the client is running".to_string());
+    }
+
+    let data_directory = options.start_options.data_directory();
+    let instances = Instances::load(LAUNCHER_DIRECTORY.config_dir())
+        .await
+        .unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || disk_usage::remove_entry(&data_directory, &instances, &path))
+        .await
+        .map_err(|e| format!("unable to remove data: {:?}", e))?
+        .map_err(|e| format!("unable to remove data: {:?}", e))
+}
+
+/// Lists the recorded game sessions, oldest first
+#[tauri::command]
+pub(crate) async fn get_session_history(options: Options) -> Result<Vec<SessionRecord>, String> {
+    let data_directory = options.start_options.data_directory();
+
+    SessionHistory::new(&data_directory)
+        .load()
+        .await
+        .map_err(|e| format!("unable to load session history: {:?}", e))
+}
+
+#[tauri::command]
+pub(crate) async fn clear_session_history(options: Options) -> Result<(), String> {
+    let data_directory = options.start_options.data_directory();
+
+    SessionHistory::new(&data_directory)
+        .clear()
+        .await
+        .map_err(|e| format!("unable to clear session history: {:?}", e))
+}
+
+/// Moves the data directory to `new_path` and stores it as the custom data path
+#[tauri::command]
+pub(crate) async fn migrate_data_directory(
+    mut options: Options,
+    new_path: String,
+    window: Window,
+    app_state: tauri::State<'_, AppState>,
+) -> Result<Options, String> {
+    if app_state
+        .runners
+        .lock()
+        .map_err(|e| format!("unable to lock runners: {:?}", e))?
+        .has_active()
+    {
+        return Err("unable to migrate data directory while the client is running".to_string());
+    }
+
+    let from = options.start_options.data_directory();
+    let to = PathBuf::from(&new_path);
+
+    tokio::task::spawn_blocking(move || {
+        data_migration::migrate_data_directory(&from, &to, LAUNCHER_DIRECTORY.config_dir(), |update| {
+            if let Err(e) = window.emit("progress-update", &update) {
+                error!("Failed to report migration progress: {:?}", e);
+            }
+        })
+    })
+        .await
+        .map_err(|e| format!("unable to migrate data directory: {:?}", e))?
+        .map_err(|e| format!("unable to migrate data directory: {:?}", e))?;
+
+    options.start_options.custom_data_path = if PathBuf::from(&new_path) == LAUNCHER_DIRECTORY.data_dir() {
+        String::new()
+    } else {
+        new_path
+    };
+    options
+        .store(LAUNCHER_DIRECTORY.config_dir())
+        .await
+        .map_err(|e| format!("unable to store config data: {:?}", e))?;
+    Ok(options)
+}
+
 #[tauri::command]
 pub(crate) async fn default_data_folder_path() -> Result<String, String> {
     let data_directory = LAUNCHER_DIRECTORY.data_dir().to_str();
@@ -0,0 +1,154 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use tauri::Window;
+use tracing::{info, warn};
+
+use crate::app::client_api::Client;
+use crate::app::gui::AppState;
+use crate::app::instance::{Instance, Instances};
+use crate::app::options::Options;
+use crate::minecraft::{cache::BuildCache, launcher::StartParameter, prelauncher};
+use crate::LAUNCHER_DIRECTORY;
+
+use super::{collect_mods, launch_client, resolve_build_id};
+
+async fn load_instances() -> Result<Instances, String> {
+    Instances::load(LAUNCHER_DIRECTORY.config_dir())
+        .await
+        .map_err(|e| format!("unable to load instances: {:?}", e))
+}
+
+async fn store_instances(instances: &Instances) -> Result<(), String> {
+    instances
+        .store(LAUNCHER_DIRECTORY.config_dir())
+        .await
+        .map_err(|e| format!("unable to store instances: {:?}", e))
+}
+
+#[tauri::command]
+pub(crate) async fn get_instances() -> Result<Vec<Instance>, String> {
+    Ok(load_instances().await?.instances)
+}
+
+#[tauri::command]
+pub(crate) async fn create_instance(name: String, options: Options) -> Result<Instance, String> {
+    let mut instances = load_instances().await?;
+    let instance = instances
+        .create(name, &options)
+        .map_err(|e| format!("unable to create instance: {:#}", e))?
+        .clone();
+    store_instances(&instances).await?;
+    Ok(instance)
+}
+
+#[tauri::command]
+pub(crate) async fn clone_instance(id: String, name: String, options: Options) -> Result<Instance, String> {
+    let mut instances = load_instances().await?;
+    let instance = instances
+        .clone_instance(&id, name, &options.start_options.data_directory())
+        .await
+        .map_err(|e| format!("unable to clone instance: {:#}", e))?
+        .clone();
+    store_instances(&instances).await?;
+    Ok(instance)
+}
+
+#[tauri::command]
+pub(crate) async fn rename_instance(id: String, name: String) -> Result<(), String> {
+    let mut instances = load_instances().await?;
+    instances
+        .rename(&id, name)
+        .map_err(|e| format!("unable to rename instance: {:#}", e))?;
+    store_instances(&instances).await
+}
+
+#[tauri::command]
+pub(crate) async fn delete_instance(id: String, options: Options) -> Result<(), String> {
+    let mut instances = load_instances().await?;
+    instances
+        .delete(&id, &options.start_options.data_directory())
+        .await
+        .map_err(|e| format!("unable to delete instance: {:#}", e))?;
+    store_instances(&instances).await
+}
+
+/// Stores the changed settings of an instance, the name is changed with [rename_instance]
+#[tauri::command]
+pub(crate) async fn store_instance(instance: Instance) -> Result<(), String> {
+    let mut instances = load_instances().await?;
+    let stored = instances
+        .get_mut(&instance.id)
+        .map_err(|e| format!("unable to store instance: {:#}", e))?;
+    *stored = Instance {
+        name: stored.name.clone(),
+        ..instance
+    };
+    store_instances(&instances).await
+}
+
+#[tauri::command]
+pub(crate) async fn run_instance(
+    client: Client,
+    id: String,
+    mut options: Options,
+    window: Window,
+    app_state: tauri::State<'_, AppState>,
+) -> Result<u32, String> {
+    let instances = load_instances().await?;
+    let instance = instances
+        .get(&id)
+        .map_err(|e| format!("unable to run instance: {:#}", e))?;
+    instance.apply(&mut options);
+
+    let build_id = resolve_build_id(&client, &options, None)
+        .await
+        .map_err(|e| format!("{:#}", e))?;
+    let branch_options = options.version_options.options.clone();
+
+    let mut parameters = StartParameter::from_options(client, options)
+        .await
+        .map_err(|e| format!("{:#}", e))?;
+    parameters.instance_directory = Some(instance.game_directory(&parameters.data_directory()));
+    parameters.instance_name = Some(instance.name.clone());
+
+    info!("Loading launch manifest of instance {}...", instance.name);
+    let launch_manifest = prelauncher::load_launch_manifest(build_id, &parameters).await.map_err(|e| {
+        format!(
+            "failed to fetch launch manifest of build {}: {:?}",
+            build_id, e
+        )
+    })?;
+
+    // Offline, the mods of the last launch of this instance are used
+    let cache = BuildCache::new(&parameters.data_directory(), build_id);
+    let mods = if parameters.offline_mode {
+        cache.load_instance_mods(&instance.id).await.unwrap_or_default()
+    } else {
+        let mods = collect_mods(&parameters.client, &parameters.data_directory(), &branch_options, &launch_manifest.build)
+            .await
+            .map_err(|e| format!("unable to collect mods: {:#}", e))?;
+        if let Err(e) = cache.store_instance_mods(&instance.id, &mods).await {
+            warn!("Failed to cache mods of instance {}: {:?}", instance.name, e);
+        }
+        mods
+    };
+
+    launch_client(launch_manifest, parameters, mods, window, &app_state)
+}
@@ -0,0 +1,44 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::app::options::Options;
+use crate::minecraft::session_log::{self, SessionLogFile};
+
+/// Lists the logs of past game sessions, newest first
+#[tauri::command]
+pub(crate) async fn list_session_logs(options: Options) -> Result<Vec<SessionLogFile>, String> {
+    session_log::list_session_logs(&options.start_options.data_directory())
+        .map_err(|e| format!("unable to list session logs: {:?}", e))
+}
+
+#[tauri::command]
+pub(crate) async fn read_session_log(options: Options, name: String) -> Result<String, String> {
+    session_log::read_session_log(&options.start_options.data_directory(), &name)
+        .map_err(|e| format!("unable to read session log: {:?}", e))
+}
+
+/// Opens the session log in the default editor of the system
+#[tauri::command]
+pub(crate) async fn open_session_log(options: Options, name: String) -> Result<(), String> {
+    let path = session_log::session_log_path(&options.start_options.data_directory(), &name)
+        .map_err(|e| format!("unable to open session log: {:?}", e))?;
+
+    tauri_plugin_opener::open_path(path, None::<&str>)
+        .map_err(|e| format!("unable to open session log: {:?}", e))
+}
@@ -20,13 +20,19 @@
 pub(crate) mod auth;
 pub(crate) mod client;
 pub(crate) mod data;
+pub(crate) mod instance;
+pub(crate) mod logs;
 pub(crate) mod system;
 pub(crate) mod minecraft_installation;
+pub(crate) mod runner;
 pub(crate) mod updater;
 
 pub(crate) use auth::*;
 pub(crate) use client::*;
 pub(crate) use data::*;
+pub(crate) use instance::*;
+pub(crate) use logs::*;
 pub(crate) use system::*;
 pub(crate) use minecraft_installation::*;
+pub(crate) use runner::*;
 pub(crate) use updater::*;
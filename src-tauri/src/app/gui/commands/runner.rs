@@ -0,0 +1,70 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::app::gui::runner::{focus_process, RunnerInfo};
+use crate::app::gui::AppState;
+use crate::minecraft::java::output::OutputLine;
+
+/// Lists the launched instances, including recently finished ones
+#[tauri::command]
+pub(crate) async fn list_running_instances(
+    app_state: tauri::State<'_, AppState>,
+) -> Result<Vec<RunnerInfo>, String> {
+    let runners = app_state
+        .runners
+        .lock()
+        .map_err(|e| format!("unable to lock runners: {:?}", e))?;
+    Ok(runners.list())
+}
+
+/// Brings the game window of the instance to the front
+#[tauri::command]
+pub(crate) async fn focus_running_instance(
+    instance_id: u32,
+    app_state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let pid = app_state
+        .runners
+        .lock()
+        .map_err(|e| format!("unable to lock runners: {:?}", e))?
+        .get(instance_id)
+        .and_then(|instance| instance.pid)
+        .ok_or_else(|| format!("instance {} has no running process", instance_id))?;
+
+    focus_process(pid)
+        .await
+        .map_err(|e| format!("unable to focus instance: {:?}", e))
+}
+
+/// Returns the buffered output of the instance, new output follows as `instance-output` events
+#[tauri::command]
+pub(crate) async fn get_running_instance_output(
+    instance_id: u32,
+    app_state: tauri::State<'_, AppState>,
+) -> Result<Vec<OutputLine>, String> {
+    let runners = app_state
+        .runners
+        .lock()
+        .map_err(|e| format!("unable to lock runners: {:?}", e))?;
+
+    runners
+        .get(instance_id)
+        .map(|instance| instance.output.iter().cloned().collect())
+        .ok_or_else(|| format!("unknown instance {}", instance_id))
+}
@@ -16,9 +16,12 @@
  * You should have received a copy of the GNU General Public License
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
-use crate::app::client_api::Client;
+use crate::app::client_api::{api_endpoints, Client};
+use crate::app::options::Options;
+use crate::minecraft::cache::BuildCache;
+use crate::minecraft::java::{discover_java, InstalledJava};
 use crate::{utils, LAUNCHER_VERSION};
-use tracing::{debug, debug_span, info};
+use tracing::{debug, debug_span, info, warn};
 
 #[tauri::command]
 pub(crate) async fn get_launcher_version() -> Result<String, String> {
@@ -26,9 +29,26 @@ pub(crate) async fn get_launcher_version() -> Result<String, String> {
 }
 
 #[tauri::command]
-pub(crate) async fn setup_client(session_token: String) -> Result<Client, String> {
-    Client::lookup(session_token)
-        .await
+pub(crate) async fn setup_client(options: Options) -> Result<Client, String> {
+    let launcher_options = options.launcher_options;
+    let offline_endpoint = api_endpoints(&launcher_options.api_url).remove(0);
+    if launcher_options.offline_mode {
+        // Skip looking for a reachable endpoint, the launch is done from cache
+        return Ok(Client::offline(&offline_endpoint, launcher_options.session_token));
+    }
+
+    let error = match Client::lookup(launcher_options.session_token.clone(), &launcher_options.api_url).await {
+        Ok(client) => return Ok(client),
+        Err(e) => e,
+    };
+
+    // Builds which have been launched before can still be launched from cache
+    if !BuildCache::has_cached_builds(&options.start_options.data_directory()).await {
+        return Err(error);
+    }
+
+    warn!("LiquidBounce API is not reachable, launching from cache: {}", error);
+    Ok(Client::offline(&offline_endpoint, launcher_options.session_token))
 }
 
 #[tauri::command]
@@ -52,3 +72,9 @@ pub(crate) async fn check_system() -> Result<(), String> {
 pub(crate) fn sys_memory() -> u64 {
     utils::sys_memory() / (1024 * 1024)
 }
+
+/// Lists the Java installations found on the system
+#[tauri::command]
+pub(crate) async fn get_installed_java() -> Result<Vec<InstalledJava>, String> {
+    Ok(discover_java().await)
+}
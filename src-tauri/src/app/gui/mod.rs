@@ -20,27 +20,25 @@
 use std::sync::{Arc, Mutex};
 
 use commands::*;
+use runner::{RunnerRegistry, ShareableRegistry};
 use tauri::Window;
 
 pub type ShareableWindow = Arc<Mutex<Window>>;
 
-pub struct RunnerInstance {
-    pub terminator: tokio::sync::oneshot::Sender<()>,
-}
-
 pub struct AppState {
-    pub runner_instance: Arc<Mutex<Option<RunnerInstance>>>,
+    pub runners: ShareableRegistry,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
-            runner_instance: Arc::new(Mutex::new(None)),
+            runners: Arc::new(Mutex::new(RunnerRegistry::default())),
         }
     }
 }
 
-mod commands;
+pub(crate) mod commands;
+pub mod runner;
 
 /// Runs the GUI and returns when the window is closed.
 pub fn gui_main() {
@@ -55,11 +53,21 @@ pub fn gui_main() {
             setup_client,
             check_system,
             sys_memory,
+            get_installed_java,
             get_options,
             store_options,
             request_builds,
             request_mods,
             run_client,
+            dry_run,
+            repair_installation,
+            get_instances,
+            create_instance,
+            clone_instance,
+            rename_instance,
+            delete_instance,
+            store_instance,
+            run_instance,
             login_offline,
             login_microsoft_device_code,
             login_microsoft_webview,
@@ -70,8 +78,20 @@ pub fn gui_main() {
             fetch_blog_posts,
             fetch_changelog,
             clear_data,
+            collect_garbage,
+            get_disk_usage,
+            remove_disk_usage_entry,
+            migrate_data_directory,
+            get_session_history,
+            clear_session_history,
+            list_session_logs,
+            read_session_log,
+            open_session_log,
             default_data_folder_path,
             terminate,
+            list_running_instances,
+            focus_running_instance,
+            get_running_instance_output,
             get_launcher_version,
             get_custom_mods,
             install_custom_mod,
@@ -0,0 +1,335 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use tauri::{Emitter, Url};
+use tokio::process::Command;
+use tokio::sync::oneshot::Sender;
+use tracing::info;
+
+use crate::minecraft::crash::CrashReport;
+use crate::minecraft::java::output::{LogLevel, OutputLine};
+use crate::minecraft::launcher::LauncherFrontend;
+use crate::minecraft::progress::ProgressUpdate;
+
+use super::ShareableWindow;
+
+/// Lines of output kept per instance, older lines are dropped
+const OUTPUT_LIMIT: usize = 10_000;
+
+/// Instances which are no longer running are kept to look at their log, up to this many
+const FINISHED_LIMIT: usize = 10;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum RunnerStatus {
+    /// Files are downloaded and the game is prepared
+    Launching,
+    Running,
+    Exited,
+    Failed,
+}
+
+impl RunnerStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(self, RunnerStatus::Launching | RunnerStatus::Running)
+    }
+}
+
+pub struct RunnerInstance {
+    pub id: u32,
+    pub name: String,
+    pub terminator: Option<Sender<()>>,
+    pub pid: Option<u32>,
+    /// Unix timestamp of the launch
+    pub started_at: u64,
+    pub status: RunnerStatus,
+    pub output: VecDeque<OutputLine>,
+}
+
+impl RunnerInstance {
+    fn push_output(&mut self, line: OutputLine) {
+        if self.output.len() >= OUTPUT_LIMIT {
+            self.output.pop_front();
+        }
+        self.output.push_back(line);
+    }
+}
+
+/// Summary of a [RunnerInstance] for the frontend
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnerInfo {
+    pub id: u32,
+    pub name: String,
+    pub pid: Option<u32>,
+    pub started_at: u64,
+    pub status: RunnerStatus,
+}
+
+/// Game instances started from the launcher, keyed by an id counting up from 1
+#[derive(Default)]
+pub struct RunnerRegistry {
+    next_id: u32,
+    instances: BTreeMap<u32, RunnerInstance>,
+}
+
+impl RunnerRegistry {
+    /// Registers a new instance, which is launching until the game process is started
+    pub fn register(&mut self, name: String, terminator: Sender<()>) -> u32 {
+        self.next_id += 1;
+        let id = self.next_id;
+
+        self.instances.insert(id, RunnerInstance {
+            id,
+            name,
+            terminator: Some(terminator),
+            pid: None,
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+            status: RunnerStatus::Launching,
+            output: VecDeque::new(),
+        });
+        self.prune();
+        id
+    }
+
+    pub fn get(&self, id: u32) -> Option<&RunnerInstance> {
+        self.instances.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut RunnerInstance> {
+        self.instances.get_mut(&id)
+    }
+
+    pub fn has_active(&self) -> bool {
+        self.instances.values().any(|instance| instance.status.is_active())
+    }
+
+    pub fn list(&self) -> Vec<RunnerInfo> {
+        self.instances
+            .values()
+            .map(|instance| RunnerInfo {
+                id: instance.id,
+                name: instance.name.clone(),
+                pid: instance.pid,
+                started_at: instance.started_at,
+                status: instance.status,
+            })
+            .collect()
+    }
+
+    /// Sends the terminator of the instance, returns false if it is not running anymore
+    pub fn terminate(&mut self, id: u32) -> bool {
+        match self.instances.get_mut(&id).and_then(|instance| instance.terminator.take()) {
+            Some(terminator) => terminator.send(()).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn terminate_all(&mut self) {
+        for instance in self.instances.values_mut() {
+            if let Some(terminator) = instance.terminator.take() {
+                let _ = terminator.send(());
+            }
+        }
+    }
+
+    pub fn finish(&mut self, id: u32, status: RunnerStatus) {
+        if let Some(instance) = self.instances.get_mut(&id) {
+            instance.status = status;
+            instance.terminator = None;
+        }
+        self.prune();
+    }
+
+    /// Drops the oldest finished instances beyond [FINISHED_LIMIT]
+    fn prune(&mut self) {
+        let finished = self
+            .instances
+            .values()
+            .filter(|instance| !instance.status.is_active())
+            .map(|instance| instance.id)
+            .collect::<Vec<_>>();
+
+        for id in finished.iter().take(finished.len().saturating_sub(FINISHED_LIMIT)) {
+            self.instances.remove(id);
+        }
+    }
+}
+
+pub type ShareableRegistry = Arc<Mutex<RunnerRegistry>>;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct InstanceOutput<'a> {
+    instance_id: u32,
+    data: &'a OutputLine,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct InstanceCrash<'a> {
+    instance_id: u32,
+    report: &'a CrashReport,
+}
+
+///
+/// Frontend of a single instance, which records its output and status in the registry
+/// and tags everything sent to the window with the instance id.
+///
+#[derive(Clone)]
+pub struct InstanceFrontend {
+    pub id: u32,
+    pub window: ShareableWindow,
+    pub runners: ShareableRegistry,
+}
+
+impl InstanceFrontend {
+    fn output(&self, line: OutputLine) -> Result<()> {
+        let emitted = self
+            .window
+            .lock()
+            .map_err(|_| anyhow!("Window lock is poisoned"))
+            .and_then(|window| {
+                Ok(window.emit("instance-output", InstanceOutput { instance_id: self.id, data: &line })?)
+            });
+
+        if let Some(instance) = self.runners.lock().unwrap().get_mut(self.id) {
+            instance.push_output(line);
+        }
+        emitted
+    }
+
+    /// Tells the window to reload the list of instances
+    pub fn instances_changed(&self) {
+        if let Ok(window) = self.window.lock() {
+            let _ = window.emit("instances-changed", self.id);
+        }
+    }
+
+    pub fn finish(&self, status: RunnerStatus) {
+        self.runners.lock().unwrap().finish(self.id, status);
+        self.instances_changed();
+    }
+}
+
+impl LauncherFrontend for InstanceFrontend {
+    fn on_output(&self, line: &OutputLine) -> Result<()> {
+        line.log(&format!("[{}] ", self.id));
+        self.output(line.clone())
+    }
+
+    fn on_progress(&self, progress_update: ProgressUpdate) -> Result<()> {
+        self.window
+            .lock()
+            .map_err(|_| anyhow!("Window lock is poisoned"))?
+            .emit("progress-update", &progress_update)?;
+
+        // Check if progress update is label update
+        if let ProgressUpdate::SetLabel(label) = progress_update {
+            self.on_log(&label)?;
+        }
+        Ok(())
+    }
+
+    fn on_log(&self, msg: &str) -> Result<()> {
+        info!("[{}] {}", self.id, msg);
+        self.output(OutputLine::launcher(LogLevel::Info, msg))
+    }
+
+    fn on_process_started(&self, pid: Option<u32>) {
+        if let Some(instance) = self.runners.lock().unwrap().get_mut(self.id) {
+            instance.pid = pid;
+            instance.status = RunnerStatus::Running;
+        }
+        self.instances_changed();
+    }
+
+    fn on_crash(&self, report: &CrashReport) {
+        if let Ok(window) = self.window.lock() {
+            let _ = window.emit("client-crash", InstanceCrash { instance_id: self.id, report });
+        }
+    }
+
+    fn hide_window(&self) {
+        self.window.hide_window();
+    }
+
+    fn show_download_page(&self, url: Url) -> impl Future<Output = Result<String>> {
+        self.window.show_download_page(url)
+    }
+}
+
+///
+/// Brings the window of the process to the front, using the tools available on each platform
+///
+pub async fn focus_process(pid: u32) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    let output = {
+        // Don't flash a console window
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                &format!("(New-Object -ComObject WScript.Shell).AppActivate({})", pid),
+            ])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .await?
+    };
+
+    #[cfg(target_os = "macos")]
+    let output = Command::new("osascript")
+        .args([
+            "-e",
+            &format!(
+                "tell application \"System Events\" to set frontmost of (first process whose unix id is {}) to true",
+                pid
+            ),
+        ])
+        .output()
+        .await?;
+
+    // Only works on X11, Wayland does not allow other applications to take focus
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let output = Command::new("xdotool")
+        .args(["search", "--onlyvisible", "--pid", &pid.to_string(), "windowactivate"])
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        bail!(
+            "Failed to focus process {}: {}",
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
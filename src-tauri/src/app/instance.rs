@@ -0,0 +1,270 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::app::options::{BranchOptions, MinecraftInstallationOptions, Options};
+use crate::minecraft::java::DistributionSelection;
+use crate::minecraft::launcher::copy_dir_all;
+
+const INSTANCES_FILE: &str = "instances.json";
+
+///
+/// A separate setup of the client with its own game directory, build, JVM and mod settings
+///
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Instance {
+    #[serde(rename = "id")]
+    pub id: String,
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "buildId", default = "default_build_id")]
+    pub build_id: i32,
+    #[serde(rename = "javaDistribution", default)]
+    pub java_distribution: DistributionSelection,
+    #[serde(rename = "jvmArgs", default)]
+    pub jvm_args: Option<Vec<String>>,
+    #[serde(rename = "memory", default = "default_memory")]
+    pub memory: u64,
+    #[serde(rename = "installation", default)]
+    pub installation: MinecraftInstallationOptions,
+    #[serde(rename = "options", default)]
+    pub options: HashMap<String, BranchOptions>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct Instances {
+    #[serde(rename = "instances", default)]
+    pub instances: Vec<Instance>,
+}
+
+impl Instance {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            build_id: default_build_id(),
+            java_distribution: DistributionSelection::default(),
+            jvm_args: None,
+            memory: default_memory(),
+            installation: MinecraftInstallationOptions::default(),
+            options: HashMap::new(),
+        }
+    }
+
+    /// Returns the game directory of the instance, which is named by its id to survive renames
+    pub fn game_directory(&self, data: &Path) -> PathBuf {
+        data.join("instances").join(&self.id)
+    }
+
+    /// Replaces the start and version options with the settings of this instance
+    pub fn apply(&self, options: &mut Options) {
+        options.start_options.java_distribution = self.java_distribution.clone();
+        options.start_options.jvm_args = self.jvm_args.clone();
+        options.start_options.memory = self.memory;
+        options.start_options.installation = self.installation.clone();
+        options.version_options.build_id = self.build_id;
+        options.version_options.options = self.options.clone();
+    }
+}
+
+impl Instances {
+    pub async fn load(app_data: &Path) -> Result<Self> {
+        let path = app_data.join(INSTANCES_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let file_content = fs::read(path).await?;
+        let instances = serde_json::from_slice::<Self>(&file_content)?;
+        info!("Successfully loaded {} instances from file", instances.instances.len());
+        Ok(instances)
+    }
+
+    pub async fn store(&self, app_data: &Path) -> Result<()> {
+        // Written next to the instances and renamed, so they are never left half written
+        let path = app_data.join(INSTANCES_FILE);
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_string(&self)?)
+            .await
+            .with_context(|| format!("Failed to write {}", temporary.display()))?;
+        fs::rename(&temporary, &path)
+            .await
+            .with_context(|| format!("Failed to replace {}", path.display()))
+    }
+
+    pub fn get(&self, id: &str) -> Result<&Instance> {
+        self.instances
+            .iter()
+            .find(|instance| instance.id == id)
+            .with_context(|| format!("instance {} does not exist", id))
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Result<&mut Instance> {
+        self.instances
+            .iter_mut()
+            .find(|instance| instance.id == id)
+            .with_context(|| format!("instance {} does not exist", id))
+    }
+
+    /// Returns the Java distribution selected by the launcher and by every instance
+    pub fn java_distributions(&self, options: &Options) -> Vec<DistributionSelection> {
+        std::iter::once(&options.start_options.java_distribution)
+            .chain(self.instances.iter().map(|instance| &instance.java_distribution))
+            .cloned()
+            .collect()
+    }
+
+    /// Finds an instance by its id or its name
+    pub fn find(&self, id_or_name: &str) -> Option<&Instance> {
+        self.instances
+            .iter()
+            .find(|instance| instance.id == id_or_name)
+            .or_else(|| self.instances.iter().find(|instance| instance.name == id_or_name))
+    }
+
+    /// Creates a new instance, seeded with the current settings of the launcher
+    pub fn create(&mut self, name: String, options: &Options) -> Result<&Instance> {
+        self.check_name(&name)?;
+
+        let mut instance = Instance::new(name);
+        instance.java_distribution = options.start_options.java_distribution.clone();
+        instance.jvm_args = options.start_options.jvm_args.clone();
+        instance.memory = options.start_options.memory;
+        instance.installation = options.start_options.installation.clone();
+        instance.build_id = options.version_options.build_id;
+        instance.options = options.version_options.options.clone();
+
+        self.instances.push(instance);
+        Ok(self.instances.last().unwrap())
+    }
+
+    /// Creates a copy of the instance with a new id, including its game directory
+    pub async fn clone_instance(&mut self, id: &str, name: String, data: &Path) -> Result<&Instance> {
+        self.check_name(&name)?;
+
+        let source = self.get(id)?;
+        let mut instance = source.clone();
+        instance.id = Uuid::new_v4().to_string();
+        instance.name = name;
+
+        let source_directory = source.game_directory(data);
+        let target_directory = instance.game_directory(data);
+        if source_directory.exists() {
+            tokio::task::spawn_blocking(move || copy_dir_all(source_directory, target_directory))
+                .await?
+                .context("unable to copy game directory")?;
+        }
+
+        self.instances.push(instance);
+        Ok(self.instances.last().unwrap())
+    }
+
+    pub fn rename(&mut self, id: &str, name: String) -> Result<()> {
+        if self.get(id)?.name == name {
+            return Ok(());
+        }
+
+        self.check_name(&name)?;
+        self.get_mut(id)?.name = name;
+        Ok(())
+    }
+
+    /// Removes the instance and its game directory
+    pub async fn delete(&mut self, id: &str, data: &Path) -> Result<()> {
+        let game_directory = self.get(id)?.game_directory(data);
+        if game_directory.exists() {
+            fs::remove_dir_all(&game_directory)
+                .await
+                .context("unable to delete game directory")?;
+        }
+
+        self.instances.retain(|instance| instance.id != id);
+        Ok(())
+    }
+
+    fn check_name(&self, name: &str) -> Result<()> {
+        if name.trim().is_empty() {
+            bail!("instance name must not be empty");
+        }
+
+        if self.instances.iter().any(|instance| instance.name == name) {
+            bail!("instance {} already exists", name);
+        }
+
+        Ok(())
+    }
+}
+
+fn default_build_id() -> i32 {
+    -1
+}
+
+fn default_memory() -> u64 {
+    4096
+}
+
+// The links are created the same way on Windows, but need developer mode there
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn clone_keeps_linked_folders() {
+        let data = std::env::temp_dir().join(format!("liquidlauncher-instance-clone-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&data);
+        let vanilla_saves = data.join("vanilla").join("saves");
+        std::fs::create_dir_all(vanilla_saves.join("World")).unwrap();
+
+        let mut instances = Instances::default();
+        let source = instances.create("Source".to_string(), &Options::default()).unwrap().clone();
+        let source_directory = source.game_directory(&data);
+        std::fs::create_dir_all(source_directory.join("config")).unwrap();
+        std::fs::write(source_directory.join("config").join("client.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(&vanilla_saves, source_directory.join("saves")).unwrap();
+
+        let clone = instances
+            .clone_instance(&source.id, "Clone".to_string(), &data)
+            .await
+            .unwrap()
+            .clone();
+        let clone_directory = clone.game_directory(&data);
+
+        assert_ne!(clone.id, source.id);
+        assert_eq!(
+            std::fs::read_to_string(clone_directory.join("config").join("client.json")).unwrap(),
+            "{}"
+        );
+        let saves = clone_directory.join("saves");
+        assert!(saves.is_symlink());
+        assert_eq!(std::fs::read_link(&saves).unwrap(), vanilla_saves);
+        assert!(saves.join("World").exists());
+
+        std::fs::remove_dir_all(&data).unwrap();
+    }
+}
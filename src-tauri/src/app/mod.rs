@@ -17,9 +17,13 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
+pub mod cli;
 pub mod gui;
 
+pub mod instance;
 pub mod options;
 pub mod webview;
 pub mod client_api;
+pub mod disk_usage;
+pub mod data_migration;
 pub mod client_api_target;
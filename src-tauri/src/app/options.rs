@@ -17,10 +17,14 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use crate::minecraft::java::DistributionSelection;
-use crate::{auth::ClientAccount, minecraft::auth::MinecraftAccount};
+use crate::utils::{set_mirror_rules, MirrorRule};
+use crate::{auth::ClientAccount, minecraft::auth::MinecraftAccount, LAUNCHER_DIRECTORY};
 use anyhow::Result;
 use rand::distr::{Alphanumeric, SampleString};
 use serde::{Deserialize, Serialize};
@@ -87,6 +91,15 @@ pub(crate) struct LauncherOptions {
     pub keep_launcher_open: bool,
     #[serde(rename = "sessionToken", default = "random_token")]
     pub session_token: String,
+    #[serde(rename = "offlineMode", default)]
+    pub offline_mode: bool,
+    #[serde(rename = "apiUrl", default)]
+    pub api_url: String,
+    #[serde(rename = "mirrors", default)]
+    pub mirrors: Vec<MirrorRule>,
+    /// Replaces the Log4j configuration of Mojang with our own, see [crate::minecraft::launcher::logging]
+    #[serde(rename = "structuredLogging", default)]
+    pub structured_logging: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -111,6 +124,7 @@ impl Options {
 
         if let Ok(options) = serde_json::from_slice::<Self>(&file_content) {
             info!("Successfully loaded options from file");
+            options.apply_mirror_rules();
             return Ok(options);
         }
         Ok(serde_json::from_slice::<Self>(&file_content)?)
@@ -119,8 +133,25 @@ impl Options {
     pub async fn store(&self, app_data: &Path) -> Result<()> {
         // store the options in the file
         fs::write(app_data.join("options.json"), serde_json::to_string(&self)?).await?;
+        self.apply_mirror_rules();
         Ok(())
     }
+
+    /// The mirror rules apply to every download, so they are set whenever the options are loaded or changed
+    fn apply_mirror_rules(&self) {
+        set_mirror_rules(self.launcher_options.mirrors.clone());
+    }
+}
+
+impl StartOptions {
+    /// Returns the custom data path if set, otherwise the default data directory of the launcher
+    pub fn data_directory(&self) -> PathBuf {
+        if !self.custom_data_path.is_empty() {
+            PathBuf::from(&self.custom_data_path)
+        } else {
+            LAUNCHER_DIRECTORY.data_dir().to_path_buf()
+        }
+    }
 }
 
 impl Default for StartOptions {
@@ -152,7 +183,11 @@ impl Default for LauncherOptions {
             show_nightly_builds: false,
             keep_launcher_open: false,
             concurrent_downloads: 10,
-            session_token: random_token()
+            session_token: random_token(),
+            offline_mode: false,
+            api_url: String::new(),
+            mirrors: vec![],
+            structured_logging: false,
         }
     }
 }
@@ -18,7 +18,7 @@
  */
 
 use crate::minecraft::{
-    launcher::LauncherData,
+    launcher::{LauncherData, LauncherFrontend},
     progress::{ProgressReceiver, ProgressUpdate},
 };
 use anyhow::{anyhow, bail, Context, Result};
@@ -39,9 +39,9 @@ use super::gui::ShareableWindow;
 
 const MAX_DOWNLOAD_ATTEMPTS: u8 = 2;
 
-pub async fn open_download_page(
+pub async fn open_download_page<D: LauncherFrontend>(
     url: &str,
-    launcher_data: &LauncherData<ShareableWindow>,
+    launcher_data: &LauncherData<D>,
 ) -> Result<String> {
     let download_page: Url = url.parse()
         .context("Failed to parse download page URL")?;
@@ -64,7 +64,7 @@ pub async fn open_download_page(
             count, MAX_DOWNLOAD_ATTEMPTS
         )));
 
-        match show_webview(download_page.clone(), &launcher_data.data).await {
+        match launcher_data.data.show_download_page(download_page.clone()).await {
             Ok(pid) => break pid,
             Err(e) => {
                 launcher_data.log(&format!("Failed to open download page: {:?}", e));
@@ -76,7 +76,7 @@ pub async fn open_download_page(
     Ok(url)
 }
 
-async fn show_webview(url: Url, window: &Arc<Mutex<tauri::Window>>) -> Result<String> {
+pub(crate) async fn show_webview(url: Url, window: &Arc<Mutex<tauri::Window>>) -> Result<String> {
     let window = window
         .lock()
         .map_err(|_| anyhow!("Failed to lock window"))?;
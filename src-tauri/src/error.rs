@@ -25,6 +25,24 @@ pub enum LauncherError {
     InvalidVersionProfile(String),
     #[error("Unknown template parameter: {0}")]
     UnknownTemplateParameter(String),
+    #[error("The custom Java at {path} could not be started: {reason}")]
+    InvalidCustomJava { path: String, reason: String },
+    #[error("The custom Java at {path} is Java {found}, but this version requires Java {required}")]
+    IncompatibleJavaVersion { path: String, found: u32, required: u32 },
+    #[error("The custom Java at {path} is built for {found}, but this system requires {required}")]
+    IncompatibleJavaArchitecture { path: String, found: String, required: String },
+}
+
+impl LauncherError {
+    /// Errors caused by the custom Java selection, which can be solved by using the automatic distribution
+    pub fn is_custom_java_error(&self) -> bool {
+        matches!(
+            self,
+            LauncherError::InvalidCustomJava { .. }
+                | LauncherError::IncompatibleJavaVersion { .. }
+                | LauncherError::IncompatibleJavaArchitecture { .. }
+        )
+    }
 }
 
 pub fn map_into_connection_error(e: Error) -> Error {
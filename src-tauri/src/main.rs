@@ -21,16 +21,21 @@
     windows_subsystem = "windows"
 )]
 
+use crate::app::cli::{cli_main, CliCommand};
 use crate::app::gui::gui_main;
+use crate::minecraft::java::output::GAME_OUTPUT_TARGET;
 use crate::utils::{OS, OS_VERSION};
 use anyhow::Result;
 use directories::ProjectDirs;
 use once_cell::sync::Lazy;
 use reqwest::Client;
-use std::io;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
+use std::{env, io};
+use tracing::level_filters::LevelFilter;
 use tracing::{debug, debug_span, error, info};
 use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Layer;
 use utils::ARCHITECTURE;
 
 pub mod app;
@@ -41,13 +46,42 @@ mod error;
 mod utils;
 
 const LAUNCHER_VERSION: &str = env!("CARGO_PKG_VERSION");
-static LAUNCHER_DIRECTORY: Lazy<ProjectDirs> =
-    Lazy::new(
-        || match ProjectDirs::from("net", "CCBlueX", "LiquidLauncher") {
-            Some(proj_dirs) => proj_dirs,
-            None => panic!("no application directory"),
-        },
-    );
+
+/// Environment variables which override the application directories, e.g. to run the tests in isolation
+const DATA_DIRECTORY_ENV: &str = "LIQUIDLAUNCHER_DATA_DIR";
+const CONFIG_DIRECTORY_ENV: &str = "LIQUIDLAUNCHER_CONFIG_DIR";
+
+static LAUNCHER_DIRECTORY: Lazy<LauncherDirectory> = Lazy::new(|| {
+    let proj_dirs = match ProjectDirs::from("net", "CCBlueX", "LiquidLauncher") {
+        Some(proj_dirs) => proj_dirs,
+        None => panic!("no application directory"),
+    };
+
+    LauncherDirectory {
+        data_dir: env::var_os(DATA_DIRECTORY_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| proj_dirs.data_dir().to_path_buf()),
+        config_dir: env::var_os(CONFIG_DIRECTORY_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| proj_dirs.config_dir().to_path_buf()),
+    }
+});
+
+/// Default data and config directory of the launcher
+struct LauncherDirectory {
+    data_dir: PathBuf,
+    config_dir: PathBuf,
+}
+
+impl LauncherDirectory {
+    fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+}
 
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
@@ -66,7 +100,15 @@ static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
 });
 
 pub fn main() -> Result<()> {
-    use tracing_subscriber::{fmt, EnvFilter};
+    use tracing_subscriber::{filter::filter_fn, fmt, EnvFilter};
+
+    // Release builds on Windows are no console application, so the output would be lost
+    #[cfg(windows)]
+    if env::args().len() > 1 {
+        attach_console();
+    }
+
+    let command = CliCommand::parse(env::args().skip(1))?;
 
     let logs = LAUNCHER_DIRECTORY.data_dir().join("logs");
     if let Err(e) = utils::clean_directory(&logs, 7) {
@@ -77,15 +119,24 @@ pub fn main() -> Result<()> {
 
     let subscriber = tracing_subscriber::registry()
         .with(EnvFilter::from("liquidlauncher=debug"))
-        .with(
+        .with(command.is_none().then(|| {
             fmt::Layer::new()
                 .with_ansi(true)
-                .with_writer(io::stdout),
-        )
+                .with_writer(io::stdout)
+        }))
+        // The command line prints its own output to stdout, only report problems on stderr
+        .with(command.is_some().then(|| {
+            fmt::Layer::new()
+                .with_ansi(true)
+                .with_writer(io::stderr)
+                .with_filter(LevelFilter::WARN)
+        }))
+        // Game output has its own file per session
         .with(
             fmt::Layer::new()
                 .with_ansi(false)
-                .with_writer(file_appender),
+                .with_writer(file_appender)
+                .with_filter(filter_fn(|metadata| metadata.target() != GAME_OUTPUT_TARGET)),
         );
     tracing::subscriber::set_global_default(subscriber).expect("Unable to set a global subscriber");
 
@@ -103,7 +154,14 @@ pub fn main() -> Result<()> {
         mkdir!(LAUNCHER_DIRECTORY.data_dir());
         mkdir!(LAUNCHER_DIRECTORY.config_dir());
 
-        info!(parent: &span, "Starting GUI using Tauri framework {}", tauri::VERSION);
+        if command.is_none() {
+            info!(parent: &span, "Starting GUI using Tauri framework {}", tauri::VERSION);
+        }
+    }
+
+    if let Some(command) = command {
+        // Run headless from the command line
+        return cli_main(command);
     }
 
     // Start the GUI
@@ -112,3 +170,14 @@ pub fn main() -> Result<()> {
     info!("Launcher exited");
     Ok(())
 }
+
+/// Writes the output of the command line to the console the launcher was started from
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // Fails if there is no parent console or the process already has one, both are fine
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
@@ -0,0 +1,144 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::fs;
+
+use crate::app::client_api::{Build, LaunchManifest, LoaderMod};
+use crate::minecraft::version::{VersionManifest, VersionProfile};
+
+const LAUNCH_MANIFEST: &str = "launch_manifest.json";
+const VERSION_PROFILE: &str = "version_profile.json";
+const VERSION_MANIFEST: &str = "version_manifest.json";
+const MODS: &str = "mods.json";
+
+fn instance_mods_file(instance_id: &str) -> String {
+    format!("mods_{}.json", instance_id)
+}
+
+///
+/// Everything fetched from the LiquidBounce API and Mojang to launch a build,
+/// stored after the last successful launch so the build can be launched again without a connection.
+///
+pub struct BuildCache {
+    path: PathBuf,
+}
+
+impl BuildCache {
+    pub fn new(data: &Path, build_id: u32) -> Self {
+        Self {
+            path: data.join("cache").join(build_id.to_string()),
+        }
+    }
+
+    /// Returns the builds which have been cached in the data directory, newest first
+    pub async fn cached_builds(data: &Path) -> Result<Vec<Build>> {
+        let cache_path = data.join("cache");
+        let mut builds = vec![];
+
+        if !cache_path.exists() {
+            return Ok(builds);
+        }
+
+        let mut entries = fs::read_dir(&cache_path).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let Some(build_id) = entry.file_name().to_str().and_then(|x| x.parse().ok()) else {
+                continue;
+            };
+
+            if let Ok(manifest) = Self::new(data, build_id).load_launch_manifest().await {
+                builds.push(manifest.build);
+            }
+        }
+
+        builds.sort_by(|a, b| b.build_id.cmp(&a.build_id));
+        Ok(builds)
+    }
+
+    /// Checks if any build can be launched from the cache
+    pub async fn has_cached_builds(data: &Path) -> bool {
+        Self::cached_builds(data).await.is_ok_and(|builds| !builds.is_empty())
+    }
+
+    pub async fn load_launch_manifest(&self) -> Result<LaunchManifest> {
+        self.load(LAUNCH_MANIFEST).await
+    }
+
+    /// Stores the launch manifest as it was received from the API,
+    /// because [LaunchManifest] does not serialize into the same format.
+    pub async fn store_launch_manifest(&self, manifest: &serde_json::Value) -> Result<()> {
+        self.store(LAUNCH_MANIFEST, manifest).await
+    }
+
+    /// Loads the version profile, which has already been merged with the profile it inherits from
+    pub async fn load_version_profile(&self) -> Result<VersionProfile> {
+        self.load(VERSION_PROFILE).await
+    }
+
+    pub async fn store_version_profile(&self, profile: &VersionProfile) -> Result<()> {
+        self.store(VERSION_PROFILE, profile).await
+    }
+
+    pub async fn load_version_manifest(&self) -> Result<VersionManifest> {
+        self.load(VERSION_MANIFEST).await
+    }
+
+    pub async fn store_version_manifest(&self, manifest: &VersionManifest) -> Result<()> {
+        self.store(VERSION_MANIFEST, manifest).await
+    }
+
+    /// Loads the additional mods the build has been launched with
+    pub async fn load_mods(&self) -> Result<Vec<LoaderMod>> {
+        self.load(MODS).await
+    }
+
+    pub async fn store_mods(&self, mods: &[LoaderMod]) -> Result<()> {
+        self.store(MODS, mods).await
+    }
+
+    /// Loads the additional mods an instance has last been launched with on this build,
+    /// the mods of the build are shared by every instance and carry the states of whichever launched last
+    pub async fn load_instance_mods(&self, instance_id: &str) -> Result<Vec<LoaderMod>> {
+        self.load(&instance_mods_file(instance_id)).await
+    }
+
+    pub async fn store_instance_mods(&self, instance_id: &str, mods: &[LoaderMod]) -> Result<()> {
+        self.store(&instance_mods_file(instance_id), mods).await
+    }
+
+    async fn load<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let path = self.path.join(name);
+        let content = fs::read(&path)
+            .await
+            .with_context(|| format!("Failed to read cached {}", path.display()))?;
+
+        serde_json::from_slice(&content)
+            .with_context(|| format!("Failed to parse cached {}", path.display()))
+    }
+
+    async fn store<T: Serialize + ?Sized>(&self, name: &str, value: &T) -> Result<()> {
+        fs::create_dir_all(&self.path).await?;
+        fs::write(self.path.join(name), serde_json::to_vec(value)?).await?;
+        Ok(())
+    }
+}
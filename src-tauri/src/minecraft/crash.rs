@@ -0,0 +1,435 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::app::client_api::LoaderMod;
+
+/// Crash reports larger than this are only read up to this size, the relevant parts are at the top
+const READ_LIMIT: usize = 1024 * 1024;
+
+/// An exception line of a stack trace, e.g. `Caused by: java.lang.IllegalStateException: message`
+static EXCEPTION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:Caused by: )?((?:[a-zA-Z_$][\w$]*\.)+[\w$]*(?:Exception|Error|Throwable)(?::.*)?)$").unwrap()
+});
+/// `Mixin [sodium.mixins.json:MixinFoo] from mod sodium failed injection check`
+static MIXIN_MOD: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)from mod ([\w\-]+)").unwrap());
+/// `Suspected Mods: Sodium (sodium), Fabric API (fabric-api)`
+static SUSPECTED_MODS: Lazy<Regex> = Lazy::new(|| Regex::new(r"Suspected Mods?: (.+)").unwrap());
+/// `at me.jellysquid.mods.sodium.Foo.bar(Foo.java:12) ~[sodium-fabric-0.5.8.jar:?]`
+static FRAME_JAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*at .+\[([^\]\s:]+)\.jar").unwrap());
+/// `C  [atio6axx.dll+0x1a2b3c]` below `# Problematic frame:` of a JVM error log
+static PROBLEMATIC_FRAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^#\s+\w+\s+\[([^+\]]+)").unwrap());
+
+/// Libraries of graphics drivers, a JVM crash inside of them is a driver problem
+const GRAPHICS_DRIVERS: [&str; 7] = [
+    "atio6axx", "atioglxx", "nvoglv", "amdxx", "libgl", "libnvidia", "opengl32",
+];
+
+/// Jars which show up in every stack trace and are never the cause
+const IGNORED_JARS: [&str; 6] = ["minecraft", "client", "fabric-loader", "sponge-mixin", "lwjgl", "datafixerupper"];
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum CrashCause {
+    OutOfMemory,
+    /// The system did not have enough memory for the heap the JVM was started with
+    InsufficientSystemMemory,
+    MissingNativeLibrary,
+    GlContext,
+    MixinConflict,
+    /// A Java exception thrown by the game or a mod
+    Exception,
+    /// The JVM itself crashed, e.g. in a graphics driver
+    NativeCrash,
+    Unknown,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SuggestedFix {
+    RaiseMemory,
+    LowerMemory,
+    DisableMod { name: String },
+    ChangeJavaDistribution,
+    UpdateGraphicsDriver,
+}
+
+/// What we were able to find out about a crash of the game
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    /// Crash reports and JVM error logs written by the crashed session
+    pub files: Vec<PathBuf>,
+    pub cause: CrashCause,
+    /// The root cause of the stack trace, if there is one
+    pub exception: Option<String>,
+    /// The mod which most likely caused the crash
+    pub culprit: Option<String>,
+    pub explanation: String,
+    pub fix: Option<SuggestedFix>,
+    pub suggestion: Option<String>,
+}
+
+///
+/// Looks for crash reports written since `since` and explains the crash.
+/// `mods` are the mods which were loaded, a culprit is only suggested to be disabled if it is one of them and not required.
+/// `stderr_tail` is analyzed instead if the game crashed without writing a crash report.
+///
+pub fn analyze(
+    game_dir: &Path,
+    since: SystemTime,
+    mods: &[LoaderMod],
+    stderr_tail: &[String],
+) -> Option<CrashReport> {
+    let files = find_crash_files(game_dir, since);
+
+    let contents = files
+        .iter()
+        .filter_map(|file| match read_limited(file) {
+            Ok(content) => Some((file, content)),
+            Err(e) => {
+                warn!("Failed to read crash report {:?}: {:?}", file, e);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // JVM error logs have their own format, the stack trace analysis only applies to crash reports and logs
+    let (jvm_logs, reports): (Vec<_>, Vec<_>) = contents
+        .iter()
+        .partition(|(file, _)| is_jvm_error_log(file));
+    let text = if reports.is_empty() {
+        // e.g. the game failed before it was able to write one, the cause is usually the last thing it printed
+        stderr_tail.join("\n")
+    } else {
+        reports
+            .iter()
+            .map(|(_, content)| content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    if files.is_empty() && text.trim().is_empty() {
+        return None;
+    }
+
+    let exception = root_exception(&text);
+    let culprit = find_culprit(&text, mods);
+    let mut report = CrashReport {
+        files,
+        cause: CrashCause::Unknown,
+        exception,
+        culprit,
+        explanation: "The game crashed for an unknown reason.".to_string(),
+        fix: None,
+        suggestion: None,
+    };
+
+    if text.contains("java.lang.OutOfMemoryError") {
+        report.explain(
+            CrashCause::OutOfMemory,
+            "The game ran out of memory.",
+            Some(SuggestedFix::RaiseMemory),
+        );
+    } else if text.contains("UnsatisfiedLinkError")
+        || text.contains("Failed to locate library")
+        || text.contains("in java.library.path")
+    {
+        report.explain(
+            CrashCause::MissingNativeLibrary,
+            "A native library could not be loaded. This usually happens when the architecture of Java does not match the one of the native libraries.",
+            Some(SuggestedFix::ChangeJavaDistribution),
+        );
+    } else if is_gl_context_failure(&text) {
+        report.explain(
+            CrashCause::GlContext,
+            "The game was unable to create an OpenGL context. Your graphics driver might be outdated or the game is running on the wrong graphics card.",
+            Some(SuggestedFix::UpdateGraphicsDriver),
+        );
+    } else if is_mixin_conflict(&text) {
+        let fix = report.disable_culprit();
+        report.explain(
+            CrashCause::MixinConflict,
+            "A mod failed to modify the game, most likely because it conflicts with another mod.",
+            fix,
+        );
+    } else if let Some((_, content)) = jvm_logs.first() {
+        report.explain_jvm_crash(content);
+    } else if report.exception.is_some() {
+        let fix = report.disable_culprit();
+        report.explain(CrashCause::Exception, "The game crashed because of an error.", fix);
+    }
+
+    Some(report)
+}
+
+impl CrashReport {
+    fn explain(&mut self, cause: CrashCause, explanation: &str, fix: Option<SuggestedFix>) {
+        self.cause = cause;
+        self.explanation = match &self.culprit {
+            Some(culprit) if matches!(cause, CrashCause::MixinConflict | CrashCause::Exception) => {
+                format!("{} It was most likely caused by {}.", explanation, culprit)
+            }
+            _ => explanation.to_string(),
+        };
+        self.suggestion = fix.as_ref().map(|fix| fix.describe());
+        self.fix = fix;
+    }
+
+    fn disable_culprit(&self) -> Option<SuggestedFix> {
+        self.culprit
+            .clone()
+            .map(|name| SuggestedFix::DisableMod { name })
+    }
+
+    fn explain_jvm_crash(&mut self, content: &str) {
+        if content.contains("insufficient memory for the Java Runtime Environment") {
+            self.explain(
+                CrashCause::InsufficientSystemMemory,
+                "Your system does not have enough free memory for the amount of memory allocated to the game.",
+                Some(SuggestedFix::LowerMemory),
+            );
+            return;
+        }
+
+        let library = content
+            .lines()
+            .skip_while(|line| !line.starts_with("# Problematic frame:"))
+            .nth(1)
+            .and_then(|line| PROBLEMATIC_FRAME.captures(line))
+            .map(|captures| captures[1].trim().to_string());
+
+        match library {
+            Some(library) if is_graphics_driver(&library) => self.explain(
+                CrashCause::GlContext,
+                &format!("Java crashed inside of the graphics driver ({}).", library),
+                Some(SuggestedFix::UpdateGraphicsDriver),
+            ),
+            Some(library) => self.explain(
+                CrashCause::NativeCrash,
+                &format!("Java crashed inside of {}.", library),
+                Some(SuggestedFix::ChangeJavaDistribution),
+            ),
+            None => self.explain(
+                CrashCause::NativeCrash,
+                "Java itself crashed.",
+                Some(SuggestedFix::ChangeJavaDistribution),
+            ),
+        }
+    }
+}
+
+impl SuggestedFix {
+    fn describe(&self) -> String {
+        match self {
+            SuggestedFix::RaiseMemory => "Allocate more memory to the game in the settings.".to_string(),
+            SuggestedFix::LowerMemory => "Allocate less memory to the game in the settings or close other programs.".to_string(),
+            SuggestedFix::DisableMod { name } => format!("Disable {} and launch again.", name),
+            SuggestedFix::ChangeJavaDistribution => "Select a different Java distribution in the settings.".to_string(),
+            SuggestedFix::UpdateGraphicsDriver => "Update your graphics driver and make sure the game runs on your dedicated graphics card.".to_string(),
+        }
+    }
+}
+
+///
+/// Crash reports in `crash-reports` and JVM error logs (`hs_err_pid*.log`) in the game directory
+/// which were written since `since`, newest first
+///
+fn find_crash_files(game_dir: &Path, since: SystemTime) -> Vec<PathBuf> {
+    let crash_reports = fs::read_dir(game_dir.join("crash-reports"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"));
+    let jvm_logs = fs::read_dir(game_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_jvm_error_log(path));
+
+    let mut files = crash_reports
+        .chain(jvm_logs)
+        .filter_map(|path| {
+            let modified = path.metadata().and_then(|metadata| metadata.modified()).ok()?;
+            (modified >= since).then_some((modified, path))
+        })
+        .collect::<Vec<_>>();
+    files.sort_by(|(a, _), (b, _)| b.cmp(a));
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+fn is_jvm_error_log(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("hs_err_pid") && name.ends_with(".log"))
+}
+
+fn read_limited(path: &Path) -> std::io::Result<String> {
+    let mut content = fs::read(path)?;
+    content.truncate(READ_LIMIT);
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+/// The innermost exception of the first stack trace, which is the one that actually went wrong
+fn root_exception(text: &str) -> Option<String> {
+    let mut exception = None;
+    for line in text.lines().map(str::trim) {
+        let Some(captures) = EXCEPTION.captures(line) else {
+            continue;
+        };
+
+        if exception.is_some() && !line.starts_with("Caused by:") {
+            // The next stack trace begins
+            break;
+        }
+        exception = Some(captures[1].to_string());
+    }
+    exception
+}
+
+///
+/// Finds the mod which most likely caused the crash, in order of confidence:
+/// the mod named by a failed mixin, the suspected mods of the crash report and the first mod jar in the stack trace.
+/// Required mods like LiquidBounce itself can't be disabled and are skipped.
+///
+fn find_culprit(text: &str, mods: &[LoaderMod]) -> Option<String> {
+    let candidates = MIXIN_MOD
+        .captures_iter(text)
+        .map(|captures| captures[1].to_string())
+        .chain(SUSPECTED_MODS.captures_iter(text).flat_map(|captures| {
+            captures[1]
+                .split(',')
+                .map(|name| name.split('(').next().unwrap_or_default().trim().to_string())
+                .collect::<Vec<_>>()
+        }))
+        .chain(text.lines().filter_map(|line| FRAME_JAR.captures(line).map(|captures| captures[1].to_string())))
+        .map(|candidate| normalize(&candidate))
+        // Very short names would match almost any mod
+        .filter(|candidate| candidate.len() >= 3 && candidate != "none")
+        .filter(|candidate| !IGNORED_JARS.iter().any(|ignored| candidate.starts_with(&normalize(ignored))));
+
+    // Mod ids and jar names rarely match the display name exactly, e.g. `sodium-fabric-0.5.8` and `Sodium`.
+    // A candidate of a required mod is skipped as a whole, so it doesn't match a similar named optional mod instead.
+    candidates
+        .filter_map(|candidate| {
+            mods.iter().find(|current_mod| {
+                let name = normalize(&current_mod.name);
+                !name.is_empty() && (candidate.starts_with(&name) || name.starts_with(&candidate))
+            })
+        })
+        .find(|current_mod| !current_mod.required)
+        .map(|current_mod| current_mod.name.clone())
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn is_gl_context_failure(text: &str) -> bool {
+    [
+        "GLFW error 65542",
+        "GLFW error 65543",
+        "Could not create context",
+        "Failed to create window",
+        "No OpenGL context",
+        "The driver does not appear to support OpenGL",
+        "Pixel format not accelerated",
+    ]
+    .iter()
+    .any(|signature| text.contains(signature))
+}
+
+fn is_mixin_conflict(text: &str) -> bool {
+    [
+        "MixinApplyError",
+        "MixinTransformerError",
+        "InvalidInjectionException",
+        "InvalidMixinException",
+        "Mixin apply failed",
+        "Mixin prepare failed",
+    ]
+    .iter()
+    .any(|signature| text.contains(signature))
+}
+
+fn is_graphics_driver(library: &str) -> bool {
+    let library = library.to_lowercase();
+    // Intel drivers are named ig*icd32.dll/ig*icd64.dll
+    (library.starts_with("ig") && library.contains("icd"))
+        || GRAPHICS_DRIVERS.iter().any(|driver| library.starts_with(driver))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::app::client_api::ModSource;
+
+    fn loader_mod(name: &str, required: bool) -> LoaderMod {
+        LoaderMod {
+            required,
+            enabled: true,
+            name: name.to_string(),
+            source: ModSource::Local { file_name: format!("{}.jar", name) },
+            sha1: None,
+            sha256: None,
+        }
+    }
+
+    #[test]
+    fn culprit_skips_required_mods() {
+        let mods = [loader_mod("LiquidBounce", true), loader_mod("Sodium", false)];
+        let text = "\
+java.lang.IllegalStateException: broken
+\tat net.ccbluex.liquidbounce.Foo.bar(Foo.java:12) ~[liquidbounce-0.20.0.jar:?]
+\tat me.jellysquid.mods.sodium.Foo.bar(Foo.java:12) ~[sodium-fabric-0.5.8.jar:?]";
+
+        assert_eq!(find_culprit(text, &mods).as_deref(), Some("Sodium"));
+        assert_eq!(find_culprit(text, &mods[..1]), None);
+    }
+
+    #[test]
+    fn stderr_is_analyzed_without_crash_report() {
+        let game_dir = std::env::temp_dir().join(format!("liquidlauncher-crash-{}", std::process::id()));
+        let mods = [loader_mod("Sodium", false)];
+        let stderr = [
+            "Exception in thread \"main\" java.lang.OutOfMemoryError: Java heap space".to_string(),
+            "\tat me.jellysquid.mods.sodium.Foo.bar(Foo.java:12) ~[sodium-fabric-0.5.8.jar:?]".to_string(),
+        ];
+
+        let report = analyze(&game_dir, SystemTime::now(), &mods, &stderr).unwrap();
+        assert!(report.files.is_empty());
+        assert_eq!(report.cause, CrashCause::OutOfMemory);
+        assert_eq!(report.fix, Some(SuggestedFix::RaiseMemory));
+
+        assert!(analyze(&game_dir, SystemTime::now(), &mods, &[]).is_none());
+    }
+}
@@ -0,0 +1,316 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use futures::future::join_all;
+use serde::Serialize;
+use tokio::process::Command;
+use tokio::time::timeout;
+use tracing::{debug, info, warn};
+
+use crate::utils::{Architecture, OperatingSystem, ARCHITECTURE, OS};
+
+/// A probed JVM can take a moment to start, but should never hang the launch
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How deep runtime folders of other launchers are searched for a Java home
+const SEARCH_DEPTH: usize = 4;
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledJava {
+    /// Java binary which is used to launch the game
+    pub path: PathBuf,
+    pub version: String,
+    pub major_version: u32,
+    pub vendor: Option<String>,
+    pub architecture: Architecture,
+}
+
+impl InstalledJava {
+    /// Whether the runtime can be used to run a build that requires `jre_version`
+    pub fn is_compatible(&self, jre_version: u32) -> bool {
+        self.major_version == jre_version && self.architecture == *ARCHITECTURE
+    }
+}
+
+///
+/// Looks for Java installations on the system and probes each of them by running it.
+///
+/// Runtimes are searched in JAVA_HOME, PATH, the usual installation folders of the operating system,
+/// SDKMAN and the runtime folders of other launchers. Installations which fail to run are skipped.
+///
+pub async fn discover_java() -> Vec<InstalledJava> {
+    // Walking the folders of other launchers touches a lot of directories
+    let binaries = match tokio::task::spawn_blocking(java_binaries).await {
+        Ok(binaries) => binaries,
+        Err(e) => {
+            warn!("Failed to search for Java installations: {:?}", e);
+            return Vec::new();
+        }
+    };
+
+    let installations = join_all(binaries.iter().map(|binary| probe_java(binary)))
+        .await
+        .into_iter()
+        .zip(&binaries)
+        .filter_map(|(result, binary)| match result {
+            Ok(java) => Some(java),
+            Err(e) => {
+                debug!("Skipping Java at {}: {:?}", binary.display(), e);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    info!("Discovered {} Java installations", installations.len());
+    installations
+}
+
+/// Lists the Java binaries of every Java home found on the system
+fn java_binaries() -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    candidate_homes()
+        .into_iter()
+        .flat_map(|home| find_java_homes(&home, SEARCH_DEPTH))
+        .map(|home| home.join("bin").join(java_executable()))
+        .chain(path_binaries())
+        .filter(|binary| binary.is_file())
+        // Symlinks like /usr/bin/java point to one of the other candidates
+        .filter(|binary| seen.insert(binary.canonicalize().unwrap_or_else(|_| binary.clone())))
+        .collect()
+}
+
+/// Finds an installed Java that is compatible with the required JRE version
+pub async fn find_installed_java(jre_version: u32) -> Option<InstalledJava> {
+    discover_java()
+        .await
+        .into_iter()
+        .filter(|java| java.is_compatible(jre_version))
+        .max_by(|a, b| compare_versions(&a.version, &b.version))
+}
+
+///
+/// Runs the Java binary to read its version, vendor and architecture.
+///
+pub async fn probe_java(binary: &Path) -> Result<InstalledJava> {
+    // The properties are printed to stderr by every Java version since 8
+    let output = timeout(
+        PROBE_TIMEOUT,
+        java_command(binary)
+            .args(["-XshowSettings:properties", "-version"])
+            .stdin(Stdio::null())
+            .output(),
+    )
+        .await
+        .context("Java did not respond in time")??;
+
+    if !output.status.success() {
+        bail!("java -version failed with {}", output.status);
+    }
+
+    let properties = String::from_utf8_lossy(&output.stderr);
+    let property = |key: &str| {
+        properties.lines().find_map(|line| {
+            let (name, value) = line.split_once('=')?;
+            (name.trim() == key).then(|| value.trim().to_string())
+        })
+    };
+
+    let version = property("java.version").context("Missing java.version")?;
+    let major_version = parse_major_version(&version)
+        .with_context(|| format!("Invalid Java version {}", version))?;
+    let architecture = match property("os.arch").as_deref() {
+        Some("amd64" | "x86_64") => Architecture::X64,
+        Some("x86" | "i386" | "i686") => Architecture::X86,
+        Some("aarch64" | "arm64") => Architecture::AARCH64,
+        Some("arm") => Architecture::ARM,
+        _ => Architecture::UNKNOWN,
+    };
+
+    Ok(InstalledJava {
+        path: launch_binary(binary),
+        version,
+        major_version,
+        vendor: property("java.vendor"),
+        architecture,
+    })
+}
+
+/// Creates a command running the Java binary without opening a console window,
+/// the launcher itself has none on Windows so java.exe would get a new one
+pub(crate) fn java_command(binary: &Path) -> Command {
+    let mut command = Command::new(binary);
+
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    command
+}
+
+/// Parses the major version from both the legacy `1.8.0_402` and the current `21.0.2` scheme
+fn parse_major_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let numbers = |version: &str| {
+        version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|part| part.parse::<u32>().ok())
+            .collect::<Vec<_>>()
+    };
+    numbers(a).cmp(&numbers(b))
+}
+
+fn java_executable() -> &'static str {
+    match OS {
+        OperatingSystem::WINDOWS => "java.exe",
+        _ => "java",
+    }
+}
+
+/// On Windows the game is started with javaw.exe to avoid opening a console window
+fn launch_binary(binary: &Path) -> PathBuf {
+    if OS == OperatingSystem::WINDOWS {
+        let javaw = binary.with_file_name("javaw.exe");
+        if javaw.is_file() {
+            return javaw;
+        }
+    }
+    binary.to_path_buf()
+}
+
+fn path_binaries() -> Vec<PathBuf> {
+    env::var_os("PATH")
+        .map(|path| env::split_paths(&path).map(|dir| dir.join(java_executable())).collect())
+        .unwrap_or_default()
+}
+
+/// Folders which either are a Java home or contain Java homes
+fn candidate_homes() -> Vec<PathBuf> {
+    let mut homes = Vec::new();
+    let home_dir = dirs::home_dir();
+
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        homes.push(PathBuf::from(java_home));
+    }
+
+    // SDKMAN
+    match env::var_os("SDKMAN_DIR") {
+        Some(sdkman) => homes.push(PathBuf::from(sdkman).join("candidates").join("java")),
+        None => homes.extend(home_dir.as_ref().map(|home| home.join(".sdkman").join("candidates").join("java"))),
+    }
+
+    // Runtimes downloaded by IDEs and build tools
+    if let Some(home) = &home_dir {
+        homes.push(home.join(".jdks"));
+        homes.push(home.join(".gradle").join("jdks"));
+    }
+
+    match OS {
+        OperatingSystem::WINDOWS => {
+            for program_files in ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"] {
+                if let Some(dir) = env::var_os(program_files).map(PathBuf::from) {
+                    homes.extend(
+                        ["Java", "Eclipse Adoptium", "Zulu", "Microsoft", "BellSoft", "Amazon Corretto", "Semeru"]
+                            .iter()
+                            .map(|vendor| dir.join(vendor)),
+                    );
+                }
+            }
+
+            // Official launcher installed from the Microsoft Store
+            if let Some(local) = dirs::data_local_dir() {
+                homes.push(
+                    local
+                        .join("Packages")
+                        .join("Microsoft.4297127D64EC6_8wekyb3d8bbwe")
+                        .join("LocalCache")
+                        .join("Local")
+                        .join("runtime"),
+                );
+            }
+        }
+        OperatingSystem::OSX => {
+            homes.push(PathBuf::from("/Library/Java/JavaVirtualMachines"));
+            homes.extend(home_dir.as_ref().map(|home| home.join("Library").join("Java").join("JavaVirtualMachines")));
+            homes.push(PathBuf::from("/opt/homebrew/opt"));
+            homes.push(PathBuf::from("/usr/local/opt"));
+        }
+        _ => {
+            homes.extend(
+                ["/usr/lib/jvm", "/usr/lib64/jvm", "/usr/java", "/opt/java", "/opt/jdk"]
+                    .iter()
+                    .map(PathBuf::from),
+            );
+        }
+    }
+
+    // Runtimes of the official launcher and Prism Launcher
+    if let Some(data) = dirs::data_dir() {
+        homes.push(data.join(".minecraft").join("runtime"));
+        homes.push(data.join("minecraft").join("runtime"));
+        homes.push(data.join("PrismLauncher").join("java"));
+    }
+    if let Some(home) = &home_dir {
+        homes.push(home.join(".minecraft").join("runtime"));
+    }
+
+    homes
+}
+
+/// Searches `root` for folders containing `bin/java`, including the macOS bundle layout
+fn find_java_homes(root: &Path, depth: usize) -> Vec<PathBuf> {
+    if root.join("bin").join(java_executable()).is_file() {
+        return vec![root.to_path_buf()];
+    }
+
+    let bundle_home = root.join("Contents").join("Home");
+    if bundle_home.join("bin").join(java_executable()).is_file() {
+        return vec![bundle_home];
+    }
+
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    std::fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .flat_map(|entry| find_java_homes(&entry.path(), depth - 1))
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -1,7 +1,9 @@
+use std::collections::HashMap;
+
 use anyhow::{anyhow, bail, Result};
-use crate::utils::{ARCHITECTURE, OS};
-use crate::HTTP_CLIENT;
+use crate::utils::{get_mirrored, head_mirrored, Architecture, Checksum, ARCHITECTURE, OS};
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 #[derive(Deserialize, Serialize, Clone)]
 #[serde(tag = "type", content = "value")]
@@ -20,7 +22,7 @@ impl Default for DistributionSelection {
     }
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub enum JavaDistribution {
     #[serde(rename = "temurin")]
     Temurin,
@@ -28,6 +30,23 @@ pub enum JavaDistribution {
     GraalVM,
     #[serde(rename = "zulu")]
     Zulu,
+    #[serde(rename = "microsoft")]
+    Microsoft,
+    #[serde(rename = "corretto")]
+    Corretto,
+    #[serde(rename = "liberica")]
+    Liberica,
+    #[serde(rename = "sapmachine")]
+    SapMachine,
+}
+
+/// Archive of a JRE, see [JavaDistribution::get_download]
+pub struct JreDownload {
+    pub architecture: Architecture,
+    /// Identifies the release, changes whenever the vendor publishes a new build
+    pub release: String,
+    pub url: String,
+    pub checksum: Option<Checksum>,
 }
 
 impl Default for JavaDistribution {
@@ -38,22 +57,24 @@ impl Default for JavaDistribution {
 }
 
 impl JavaDistribution {
-    pub async fn get_url(&self, jre_version: &u32) -> Result<String> {
-        let os_arch = ARCHITECTURE.get_simple_name()?;
+    ///
+    /// Resolves the archive of the JRE together with the checksum published by the vendor
+    ///
+    pub async fn get_download(&self, jre_version: &u32) -> Result<JreDownload> {
+        self.get_download_for(jre_version, &ARCHITECTURE).await
+    }
+
+    /// Same as [JavaDistribution::get_download], but for a build of another architecture
+    pub async fn get_download_for(&self, jre_version: &u32, arch: &Architecture) -> Result<JreDownload> {
+        let os_arch = arch.get_simple_name()?;
         let archive_type = OS.get_archive_type()?;
 
         Ok(match self {
-            JavaDistribution::Temurin => {
-                let os_name = OS.get_adoptium_name()?;
-                format!(
-                    "https://api.adoptium.net/v3/binary/latest/{}/ga/{}/{}/jre/hotspot/normal/eclipse?project=jdk",
-                    jre_version, os_name, os_arch
-                )
-            }
+            JavaDistribution::Temurin => fetch_temurin_download(*jre_version, arch).await?,
             JavaDistribution::GraalVM => {
                 let os_name = OS.get_graal_name()?;
 
-                if jre_version > &17 {
+                let url = if jre_version > &17 {
                     format!(
                         "https://download.oracle.com/graalvm/{}/latest/graalvm-jdk-{}_{}-{}_bin.{}",
                         jre_version, jre_version, os_name, os_arch, archive_type
@@ -66,11 +87,35 @@ impl JavaDistribution {
                     )
                 } else {
                     bail!("GraalVM only supports Java 17+")
+                };
+
+                // Oracle publishes the SHA256 next to every archive
+                let checksum = match fetch_checksum_file(&format!("{}.sha256", url)).await {
+                    Ok(checksum) => Some(checksum),
+                    Err(e) => {
+                        warn!("Unable to fetch the GraalVM checksum, the archive is not verified: {:?}", e);
+                        None
+                    }
+                };
+
+                // The latest link carries no version, but the checksum changes with every release
+                JreDownload {
+                    architecture: arch.clone(),
+                    release: match &checksum {
+                        Some(checksum) => {
+                            format!("graalvm-{}-{}", jre_version, checksum.chars().take(12).collect::<String>())
+                        }
+                        None => format!("graalvm-{}", jre_version),
+                    },
+                    checksum: checksum.map(Checksum::sha256),
+                    url,
                 }
             }
-            JavaDistribution::Zulu => {
-                fetch_zulu_download_url(*jre_version).await?
-            }
+            JavaDistribution::Zulu => fetch_zulu_download(*jre_version, arch).await?,
+            JavaDistribution::Microsoft => fetch_microsoft_download(*jre_version, arch).await?,
+            JavaDistribution::Corretto => fetch_corretto_download(*jre_version, arch).await?,
+            JavaDistribution::Liberica => fetch_liberica_download(*jre_version, arch).await?,
+            JavaDistribution::SapMachine => fetch_sapmachine_download(*jre_version, arch).await?,
         })
     }
 
@@ -79,6 +124,10 @@ impl JavaDistribution {
             JavaDistribution::Temurin => "temurin",
             JavaDistribution::GraalVM => "graalvm",
             JavaDistribution::Zulu => "zulu",
+            JavaDistribution::Microsoft => "microsoft",
+            JavaDistribution::Corretto => "corretto",
+            JavaDistribution::Liberica => "liberica",
+            JavaDistribution::SapMachine => "sapmachine",
         }
     }
 
@@ -87,39 +136,254 @@ impl JavaDistribution {
             JavaDistribution::Temurin => true, // Supports 8, 11, 17, 21
             JavaDistribution::GraalVM => version >= 17, // Only supports 17+
             JavaDistribution::Zulu => true,             // Community builds available for all LTS versions
+            JavaDistribution::Microsoft => matches!(version, 11 | 17 | 21 | 25), // Only LTS builds
+            JavaDistribution::Corretto => version == 8 || version >= 11, // No Corretto 9 and 10
+            JavaDistribution::Liberica => true,         // Supports 8 and newer
+            JavaDistribution::SapMachine => version >= 11, // First release was 11
         }
     }
 }
 
-async fn fetch_zulu_download_url(jre_version: u32) -> Result<String> {
+async fn fetch_temurin_download(jre_version: u32, arch: &Architecture) -> Result<JreDownload> {
+    #[derive(Deserialize)]
+    struct AdoptiumAsset {
+        binary: AdoptiumBinary,
+        release_name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct AdoptiumBinary {
+        package: AdoptiumPackage,
+    }
+
+    #[derive(Deserialize)]
+    struct AdoptiumPackage {
+        link: String,
+        checksum: Option<String>,
+    }
+
+    let os_name = OS.get_adoptium_name()?;
+    let os_arch = arch.get_simple_name()?;
+    let request_url = format!(
+        "https://api.adoptium.net/v3/assets/latest/{}/hotspot?architecture={}&image_type=jre&os={}&vendor=eclipse",
+        jre_version, os_arch, os_name
+    );
+
+    let response =
+        get_mirrored(&request_url, |request| request.header("accept", "application/json")).await?;
+
+    let assets: Vec<AdoptiumAsset> = response.json().await?;
+    let asset = assets
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No Temurin runtime available for Java {} on {}-{}", jre_version, os_name, os_arch))?;
+    let package = asset.binary.package;
+
+    Ok(JreDownload {
+        architecture: arch.clone(),
+        release: asset.release_name,
+        url: package.link,
+        checksum: package.checksum.map(Checksum::sha256),
+    })
+}
+
+async fn fetch_zulu_download(jre_version: u32, arch: &Architecture) -> Result<JreDownload> {
     #[derive(Deserialize)]
     struct AzulPackage {
+        package_uuid: String,
+        name: String,
         download_url: String,
         latest: Option<bool>,
     }
 
+    #[derive(Deserialize)]
+    struct AzulPackageDetails {
+        sha256_hash: Option<String>,
+    }
+
     let os_param = OS.get_zulu_name()?;
-    let arch_param = ARCHITECTURE.get_zulu_name()?;
+    let arch_param = arch.get_zulu_name()?;
     let request_url = format!(
         "https://api.azul.com/metadata/v1/zulu/packages/?java_version={}&os={}&arch={}&java_package_type=jre&availability_types=CA&release_status=ga&javafx_bundled=false&latest=true&page_size=1",
         jre_version, os_param, arch_param
     );
 
-    let response = HTTP_CLIENT
-        .get(&request_url)
-        .header("accept", "application/json")
-        .send()
-        .await?
-        .error_for_status()?;
+    let response =
+        get_mirrored(&request_url, |request| request.header("accept", "application/json")).await?;
 
     let packages: Vec<AzulPackage> = response.json().await?;
     if packages.is_empty() {
         bail!("No Zulu runtime available for Java {} on {}-{}", jre_version, os_param, arch_param);
     }
 
-    packages
+    let package = packages
         .into_iter()
         .find(|pkg| pkg.latest.unwrap_or(true))
-        .map(|pkg| pkg.download_url)
-        .ok_or_else(|| anyhow!("Failed to determine latest Zulu runtime download URL"))
+        .ok_or_else(|| anyhow!("Failed to determine latest Zulu runtime download URL"))?;
+
+    // The checksum is only part of the package details
+    let details: AzulPackageDetails = get_mirrored(
+        &format!("https://api.azul.com/metadata/v1/zulu/packages/{}", package.package_uuid),
+        |request| request.header("accept", "application/json"),
+    )
+    .await?
+    .json()
+    .await?;
+
+    Ok(JreDownload {
+        architecture: arch.clone(),
+        release: package.name,
+        url: package.download_url,
+        checksum: details.sha256_hash.map(Checksum::sha256),
+    })
+}
+
+/// Reads checksum files in the `<hash>  <file name>` format of sha256sum
+async fn fetch_checksum_file(url: &str) -> Result<String> {
+    let content = get_mirrored(url, |request| request).await?.text().await?;
+
+    content
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("Empty checksum file {}", url))
+}
+
+/// Follows the redirect of a `latest` link to the archive of the actual release
+async fn resolve_latest_link(url: &str) -> Result<String> {
+    let response = head_mirrored(url, |request| request).await?;
+    Ok(response.url().to_string())
+}
+
+/// Release name taken from the file name of a resolved download link
+fn file_name_of(url: &str) -> String {
+    url.rsplit('/').next().unwrap_or(url).to_string()
+}
+
+async fn fetch_microsoft_download(jre_version: u32, arch: &Architecture) -> Result<JreDownload> {
+    // Microsoft only ships JDKs
+    let latest = format!(
+        "https://aka.ms/download-jdk/microsoft-jdk-{}-{}-{}.{}",
+        jre_version,
+        OS.get_microsoft_name()?,
+        arch.get_microsoft_name()?,
+        OS.get_archive_type()?
+    );
+    let url = resolve_latest_link(&latest).await?;
+    let checksum = fetch_checksum_file(&format!("{}.sha256sum.txt", latest)).await?;
+
+    Ok(JreDownload {
+        architecture: arch.clone(),
+        release: file_name_of(&url),
+        url,
+        checksum: Some(Checksum::sha256(checksum)),
+    })
+}
+
+async fn fetch_corretto_download(jre_version: u32, arch: &Architecture) -> Result<JreDownload> {
+    let file_name = format!(
+        "amazon-corretto-{}-{}-{}-jdk.{}",
+        jre_version,
+        arch.get_corretto_name()?,
+        OS.get_corretto_name()?,
+        OS.get_archive_type()?
+    );
+    let url = resolve_latest_link(&format!("https://corretto.aws/downloads/latest/{}", file_name)).await?;
+    let checksum = fetch_checksum_file(&format!("https://corretto.aws/downloads/latest_sha256/{}", file_name)).await?;
+
+    Ok(JreDownload {
+        architecture: arch.clone(),
+        release: file_name_of(&url),
+        url,
+        checksum: Some(Checksum::sha256(checksum)),
+    })
+}
+
+async fn fetch_liberica_download(jre_version: u32, arch: &Architecture) -> Result<JreDownload> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct LibericaRelease {
+        download_url: String,
+        filename: String,
+        sha1: Option<String>,
+    }
+
+    let os_name = OS.get_liberica_name()?;
+    let (arch_name, bitness) = arch.get_liberica_name()?;
+    let request_url = format!(
+        "https://api.bell-sw.com/v1/liberica/releases?version-feature={}&version-modifier=latest&os={}&arch={}&bitness={}&package-type={}&bundle-type=jre&installation-type=archive",
+        jre_version, os_name, arch_name, bitness, OS.get_archive_type()?
+    );
+
+    let response =
+        get_mirrored(&request_url, |request| request.header("accept", "application/json")).await?;
+
+    let releases: Vec<LibericaRelease> = response.json().await?;
+    let release = releases
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No Liberica runtime available for Java {} on {}-{}{}", jre_version, os_name, arch_name, bitness))?;
+
+    Ok(JreDownload {
+        architecture: arch.clone(),
+        release: release.filename,
+        url: release.download_url,
+        checksum: release.sha1.map(Checksum::sha1),
+    })
+}
+
+async fn fetch_sapmachine_download(jre_version: u32, arch: &Architecture) -> Result<JreDownload> {
+    /// Latest releases of a major version, the file is keyed by the major version
+    #[derive(Deserialize)]
+    struct SapMachineVersion {
+        releases: Vec<SapMachineRelease>,
+    }
+
+    #[derive(Deserialize)]
+    struct SapMachineRelease {
+        tag: String,
+        /// Archive links of each platform by archive type, e.g. `linux-x64` and `tar.gz`
+        #[serde(default)]
+        jre: HashMap<String, HashMap<String, String>>,
+    }
+
+    let os_name = OS.get_sapmachine_name()?;
+    let os_arch = arch.get_sapmachine_name()?;
+    let archive_type = OS.get_archive_type()?;
+
+    // Published with the SapMachine website, unlike the GitHub API it is neither rate limited nor paginated
+    let response = get_mirrored(
+        "https://sap.github.io/SapMachine/assets/data/sapmachine-releases-latest.json",
+        |request| request.header("accept", "application/json"),
+    )
+    .await?;
+    let versions: HashMap<String, SapMachineVersion> = response.json().await?;
+
+    // Tags look like sapmachine-21.0.5, early access builds like sapmachine-25+20 are skipped
+    let tag = format!("sapmachine-{}", jre_version);
+    let url = versions
+        .get(&jre_version.to_string())
+        .into_iter()
+        .flat_map(|version| &version.releases)
+        .filter(|release| release.tag == tag || release.tag.starts_with(&format!("{}.", tag)))
+        .find_map(|release| release.jre.get(&format!("{}-{}", os_name, os_arch))?.get(archive_type))
+        .cloned()
+        .ok_or_else(|| anyhow!("No SapMachine runtime available for Java {} on {}-{}", jre_version, os_name, os_arch))?;
+
+    // The checksum file replaces the archive extension
+    let checksum_url = format!("{}.sha256.txt", url.trim_end_matches(&format!(".{}", archive_type)));
+    let checksum = match fetch_checksum_file(&checksum_url).await {
+        Ok(checksum) => Some(Checksum::sha256(checksum)),
+        Err(e) => {
+            warn!("Unable to fetch the SapMachine checksum, the archive is not verified: {:?}", e);
+            None
+        }
+    };
+
+    Ok(JreDownload {
+        architecture: arch.clone(),
+        release: file_name_of(&url),
+        url,
+        checksum,
+    })
 }
@@ -17,16 +17,74 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::io::Cursor;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, Context, Result};
 use path_absolutize::Absolutize;
+use serde::{Deserialize, Serialize};
 use tokio::fs;
+use tracing::{info, warn};
 
-use crate::utils::{download_file, tar_gz_extract, zip_extract, OperatingSystem, OS};
+use crate::utils::{
+    download_file, is_path_in_use, lock_file, tar_gz_extract, zip_extract, Architecture, OperatingSystem, ARCHITECTURE, OS,
+};
 
-use super::JavaDistribution;
+use super::{JavaDistribution, JreDownload, JreResolution};
+
+/// Records which release of a runtime is installed, stored in the runtime folder
+const METADATA_FILE: &str = "runtime.json";
+
+/// How often the vendor is asked for a newer release of an installed runtime
+const UPDATE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeMetadata {
+    /// Release as named by the vendor
+    release: String,
+    /// Folder inside the runtime folder the release was extracted to
+    folder: String,
+    /// Unix timestamp of the last update check
+    checked_at: u64,
+    /// Differs from the system architecture when the runtime is emulated
+    #[serde(default)]
+    architecture: Option<Architecture>,
+}
+
+fn runtime_path(runtimes_folder: &Path, jre_distribution: &JavaDistribution, jre_version: &u32) -> PathBuf {
+    runtimes_folder.join(format!("{}_{}", jre_distribution.get_name(), jre_version))
+}
+
+/// Held while a runtime is installed or updated, concurrent launches share the runtime and its archive
+async fn lock_runtime(
+    runtimes_folder: &Path,
+    jre_distribution: &JavaDistribution,
+    jre_version: &u32,
+) -> Result<std::fs::File> {
+    let path = runtimes_folder.join(format!("{}_{}.lock", jre_distribution.get_name(), jre_version));
+    lock_file(&path).await
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+async fn read_metadata(runtime_path: &Path) -> Option<RuntimeMetadata> {
+    let content = fs::read(runtime_path.join(METADATA_FILE)).await.ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// Replaces the metadata with a rename, so the active release is switched atomically
+async fn write_metadata(runtime_path: &Path, metadata: &RuntimeMetadata) -> Result<()> {
+    let temporary = runtime_path.join(format!("{}.tmp", METADATA_FILE));
+    fs::write(&temporary, serde_json::to_vec_pretty(metadata)?).await?;
+    fs::rename(&temporary, runtime_path.join(METADATA_FILE)).await?;
+    Ok(())
+}
 
 /// Find java binary in JRE folder
 pub async fn find_java_binary(
@@ -34,13 +92,26 @@ pub async fn find_java_binary(
     jre_distribution: &JavaDistribution,
     jre_version: &u32,
 ) -> Result<PathBuf> {
-    let runtime_path =
-        runtimes_folder.join(format!("{}_{}", jre_distribution.get_name(), jre_version));
+    let runtime_path = runtime_path(runtimes_folder, jre_distribution, jre_version);
+
+    // Runtimes installed before releases were recorded are extracted directly into the runtime folder
+    let release_path = match read_metadata(&runtime_path).await {
+        Some(metadata) => runtime_path.join(metadata.folder),
+        None => runtime_path,
+    };
 
+    java_binary_in(&release_path).await
+}
+
+/// Finds the java binary in the folder an archive was extracted to
+async fn java_binary_in(release_path: &Path) -> Result<PathBuf> {
     // Find JRE in runtime folder
-    let mut files = fs::read_dir(&runtime_path).await?;
+    let mut files = fs::read_dir(release_path).await?;
 
-    if let Some(jre_folder) = files.next_entry().await? {
+    while let Some(jre_folder) = files.next_entry().await? {
+        if !jre_folder.file_type().await?.is_dir() {
+            continue;
+        }
         let folder_path = jre_folder.path();
 
         let java_binary = match OS {
@@ -86,26 +157,217 @@ pub async fn jre_download<F>(
 where
     F: Fn(u64, u64),
 {
-    let runtime_path =
-        runtimes_folder.join(format!("{}_{}", jre_distribution.get_name(), jre_version));
+    let download = jre_distribution.get_download(jre_version).await?;
+    install_release(runtimes_folder, jre_distribution, jre_version, &download, on_progress).await
+}
 
-    if runtime_path.exists() {
-        fs::remove_dir_all(&runtime_path).await?;
+/// Installs the JRE picked by [super::resolve_download]
+pub async fn jre_install<F>(
+    runtimes_folder: &Path,
+    resolution: &JreResolution,
+    jre_version: &u32,
+    on_progress: F,
+) -> Result<PathBuf>
+where
+    F: Fn(u64, u64),
+{
+    install_release(runtimes_folder, &resolution.distribution, jre_version, &resolution.download, on_progress).await
+}
+
+///
+/// Checks the vendor for a newer release of an installed runtime, at most once per [UPDATE_INTERVAL].
+///
+/// A newer release is installed next to the current one and only becomes active once it is complete.
+/// Returns the java binary of the new release, or `None` if the installed release is up to date.
+///
+pub async fn jre_update<F>(
+    runtimes_folder: &Path,
+    jre_distribution: &JavaDistribution,
+    jre_version: &u32,
+    on_progress: F,
+) -> Result<Option<PathBuf>>
+where
+    F: Fn(u64, u64),
+{
+    let runtime_path = runtime_path(runtimes_folder, jre_distribution, jre_version);
+    let metadata = read_metadata(&runtime_path).await;
+
+    if let Some(metadata) = &metadata {
+        if now().saturating_sub(metadata.checked_at) < UPDATE_INTERVAL.as_secs() {
+            return Ok(None);
+        }
     }
-    fs::create_dir_all(&runtime_path).await?;
 
-    let url = jre_distribution.get_url(jre_version).await?;
-    let retrieved_bytes = download_file(&url, on_progress).await?;
-    let cursor = Cursor::new(&retrieved_bytes[..]);
+    // Stay on the architecture the runtime was installed for
+    let architecture = metadata
+        .as_ref()
+        .and_then(|metadata| metadata.architecture.clone())
+        .unwrap_or_else(|| ARCHITECTURE.clone());
+    let download = jre_distribution.get_download_for(jre_version, &architecture).await?;
+    match metadata {
+        Some(mut metadata) if metadata.release == download.release => {
+            let _lock = lock_runtime(runtimes_folder, jre_distribution, jre_version).await?;
+            metadata.checked_at = now();
+            write_metadata(&runtime_path, &metadata).await?;
+            // Releases replaced by an update are only removed now, a game might have still been running them.
+            // A runtime installed before releases were recorded has no folder of its own and nothing to remove.
+            if !metadata.folder.is_empty() {
+                remove_old_releases(&runtime_path, &metadata.folder).await;
+            }
+            return Ok(None);
+        }
+        None => {
+            // Installed before releases were recorded, which release it is remains unknown.
+            // It is kept as the latest release instead of being downloaded again, until the vendor publishes a newer one.
+            let _lock = lock_runtime(runtimes_folder, jre_distribution, jre_version).await?;
+            write_metadata(&runtime_path, &RuntimeMetadata {
+                release: download.release.clone(),
+                folder: String::new(),
+                checked_at: now(),
+                architecture: None,
+            })
+                .await?;
+            info!("Recorded {} JRE {} as {}", jre_distribution.get_name(), jre_version, download.release);
+            return Ok(None);
+        }
+        _ => {}
+    }
 
-    match OS {
-        OperatingSystem::WINDOWS => zip_extract(cursor, runtime_path.as_path()).await?,
-        OperatingSystem::LINUX | OperatingSystem::OSX => {
-            tar_gz_extract(cursor, runtime_path.as_path()).await?
+    info!(
+        "Updating {} JRE {} to {}",
+        jre_distribution.get_name(),
+        jre_version,
+        download.release
+    );
+    install_release(runtimes_folder, jre_distribution, jre_version, &download, on_progress)
+        .await
+        .map(Some)
+}
+
+/// Installs a release side by side with the current one and switches to it,
+/// the previous release is removed by the next update check of [jre_update]
+async fn install_release<F>(
+    runtimes_folder: &Path,
+    jre_distribution: &JavaDistribution,
+    jre_version: &u32,
+    download: &JreDownload,
+    on_progress: F,
+) -> Result<PathBuf>
+where
+    F: Fn(u64, u64),
+{
+    let runtime_path = runtime_path(runtimes_folder, jre_distribution, jre_version);
+    let folder = sanitize_filename::sanitize(&download.release);
+    let release_path = runtime_path.join(&folder);
+
+    let previous = read_metadata(&runtime_path).await.map(|metadata| metadata.release);
+    let _lock = lock_runtime(runtimes_folder, jre_distribution, jre_version).await?;
+
+    // Another launch might have installed the release while waiting for the lock,
+    // a release which was installed before is reinstalled though, e.g. to repair it
+    if let Some(metadata) = read_metadata(&runtime_path).await {
+        if metadata.release == download.release && previous.as_ref() != Some(&metadata.release) {
+            if let Ok(binary) = java_binary_in(&runtime_path.join(&metadata.folder)).await {
+                return Ok(binary);
+            }
+        }
+    }
+
+    // Leftover of an interrupted installation
+    if release_path.exists() {
+        fs::remove_dir_all(&release_path).await?;
+    }
+    fs::create_dir_all(&release_path).await?;
+
+    let binary = match extract_release(runtimes_folder, jre_distribution, jre_version, download, &release_path, on_progress).await {
+        Ok(binary) => binary,
+        Err(e) => {
+            if let Err(e) = fs::remove_dir_all(&release_path).await {
+                warn!("Failed to remove incomplete JRE {}: {:?}", release_path.display(), e);
+            }
+            return Err(e);
+        }
+    };
+
+    write_metadata(&runtime_path, &RuntimeMetadata {
+        release: download.release.clone(),
+        folder: folder.clone(),
+        checked_at: now(),
+        architecture: Some(download.architecture.clone()),
+    })
+        .await?;
+    info!("Installed {} JRE {}", jre_distribution.get_name(), download.release);
+
+    Ok(binary)
+}
+
+async fn extract_release<F>(
+    runtimes_folder: &Path,
+    jre_distribution: &JavaDistribution,
+    jre_version: &u32,
+    download: &JreDownload,
+    release_path: &Path,
+    on_progress: F,
+) -> Result<PathBuf>
+where
+    F: Fn(u64, u64),
+{
+    // Keep the archive next to the runtime, so it is extracted from disk instead of memory
+    let archive_path =
+        runtimes_folder.join(format!("{}_{}.archive", jre_distribution.get_name(), jre_version));
+    download_file(&download.url, &archive_path, on_progress).await?;
+
+    // Verify the archive against the vendor checksum before extracting anything
+    if let Some(checksum) = &download.checksum {
+        if let Err(e) = checksum.verify(&archive_path).await {
+            fs::remove_file(&archive_path).await?;
+            return Err(e.context("Downloaded JRE archive is corrupted"));
         }
-        _ => bail!("Unsupported OS"),
     }
 
+    let archive = fs::File::open(&archive_path).await?;
+    let extracted = match OS {
+        OperatingSystem::WINDOWS => zip_extract(archive, release_path).await,
+        OperatingSystem::LINUX | OperatingSystem::OSX => tar_gz_extract(archive, release_path).await,
+        _ => Err(anyhow!("Unsupported OS")),
+    };
+    fs::remove_file(&archive_path).await?;
+    extracted?;
+
     // Find JRE afterwards
-    find_java_binary(runtimes_folder, jre_distribution, jre_version).await
+    java_binary_in(release_path)
+        .await
+        .with_context(|| format!("JRE archive {} contains no java binary", download.url))
+}
+
+/// Removes every release except the active one, a release a game is still running from is removed next time
+async fn remove_old_releases(runtime_path: &Path, active: &str) {
+    let Ok(mut entries) = fs::read_dir(runtime_path).await else {
+        return;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry.file_name() == active || entry.file_name() == METADATA_FILE {
+            continue;
+        }
+
+        let path = entry.path();
+        // Unix allows deleting the files of a running JVM, which crashes once it loads a class from them
+        let in_use = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || is_path_in_use(&path)).await.unwrap_or(true)
+        };
+        if in_use {
+            info!("Keeping old JRE {} which is still in use", path.display());
+            continue;
+        }
+
+        let removed = match entry.file_type().await {
+            Ok(file_type) if file_type.is_dir() => fs::remove_dir_all(&path).await,
+            _ => fs::remove_file(&path).await,
+        };
+        if let Err(e) = removed {
+            warn!("Failed to remove old JRE {}: {:?}", path.display(), e);
+        }
+    }
 }
@@ -17,8 +17,11 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
+mod discovery;
 mod distribution;
 pub(crate) mod jre_downloader;
+pub mod output;
+mod resolution;
 mod runtime;
 
-pub use {distribution::*, jre_downloader::*, runtime::*};
+pub use {discovery::*, distribution::*, jre_downloader::*, resolution::*, runtime::*};
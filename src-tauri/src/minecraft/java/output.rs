@@ -0,0 +1,436 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{Local, TimeZone};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use tracing::{debug, error, info, trace, warn};
+
+/// Tracing target of the game output, which is written to the session logs instead of the launcher log
+pub const GAME_OUTPUT_TARGET: &str = "liquidlauncher::game";
+
+/// Lines longer than this are split, so a process writing without line breaks can't fill up the memory
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// Console pattern of Minecraft `[12:34:56] [Render thread/INFO]: message`
+/// and of Fabric `[12:34:56] [main/INFO] (Minecraft) message`
+static LOG4J_PREFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\[[\d:.]+\] \[(.+?)/(TRACE|DEBUG|INFO|WARN|ERROR|FATAL)\](?:: | \(([^)]*)\) ?)").unwrap()
+});
+
+/// `logger="net.minecraft.client.Minecraft"` of `<log4j:Event logger=".." timestamp=".." level=".." thread="..">`
+static EVENT_ATTRIBUTE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(\w+)="([^"]*)""#).unwrap());
+
+/// Events which are not closed until this size are given up on and shown as they are
+const MAX_EVENT_LENGTH: usize = 1024 * 1024;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+    /// Messages of the launcher itself, e.g. progress labels and errors
+    Launcher,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    fn parse(level: &str) -> Option<Self> {
+        match level {
+            "TRACE" => Some(LogLevel::Trace),
+            "DEBUG" => Some(LogLevel::Debug),
+            "INFO" => Some(LogLevel::Info),
+            "WARN" => Some(LogLevel::Warn),
+            "ERROR" => Some(LogLevel::Error),
+            "FATAL" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+            LogLevel::Fatal => "FATAL",
+        }
+    }
+}
+
+/// A single line of output, without the line break
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputLine {
+    pub stream: OutputStream,
+    /// Unix timestamp in milliseconds of when the launcher received the line
+    pub timestamp: u64,
+    /// Level of the Log4j prefix, continuation lines like stack traces inherit it from the line before
+    pub level: Option<LogLevel>,
+    pub thread: Option<String>,
+    pub logger: Option<String>,
+    pub text: String,
+}
+
+impl OutputLine {
+    pub fn launcher(level: LogLevel, text: &str) -> Self {
+        Self {
+            stream: OutputStream::Launcher,
+            timestamp: timestamp(),
+            level: Some(level),
+            thread: None,
+            logger: None,
+            text: text.trim_end_matches(['\r', '\n']).to_string(),
+        }
+    }
+
+    /// Level to show the line with, output without a Log4j prefix is rated by its stream
+    pub fn effective_level(&self) -> LogLevel {
+        self.level.unwrap_or(match self.stream {
+            OutputStream::Stderr => LogLevel::Error,
+            _ => LogLevel::Info,
+        })
+    }
+
+    /// Records the line in the launcher log at its level, `source` is put in front, e.g. the instance
+    pub fn log(&self, source: &str) {
+        match self.effective_level() {
+            LogLevel::Trace => trace!(target: GAME_OUTPUT_TARGET, "{}{}", source, self.text),
+            LogLevel::Debug => debug!(target: GAME_OUTPUT_TARGET, "{}{}", source, self.text),
+            LogLevel::Info => info!(target: GAME_OUTPUT_TARGET, "{}{}", source, self.text),
+            LogLevel::Warn => warn!(target: GAME_OUTPUT_TARGET, "{}{}", source, self.text),
+            LogLevel::Error | LogLevel::Fatal => error!(target: GAME_OUTPUT_TARGET, "{}{}", source, self.text),
+        }
+    }
+}
+
+fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+///
+/// Splits the output of a stream into lines. Bytes are buffered until a line is complete,
+/// so characters and lines split across reads are put back together.
+///
+pub struct LineDecoder {
+    stream: OutputStream,
+    pending: Vec<u8>,
+    /// Lines of an XML log event which has not been closed yet
+    event: Option<String>,
+    level: Option<LogLevel>,
+    thread: Option<String>,
+    logger: Option<String>,
+}
+
+impl LineDecoder {
+    pub fn new(stream: OutputStream) -> Self {
+        Self {
+            stream,
+            pending: Vec::new(),
+            event: None,
+            level: None,
+            thread: None,
+            logger: None,
+        }
+    }
+
+    /// Adds the bytes read from the stream and returns the lines which are complete now
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<OutputLine> {
+        let mut lines = Vec::new();
+        let mut start = self.pending.len();
+        self.pending.extend_from_slice(bytes);
+
+        while let Some(position) = self.pending[start..].iter().position(|byte| *byte == b'\n') {
+            let rest = self.pending.split_off(start + position + 1);
+            let line = std::mem::replace(&mut self.pending, rest);
+            self.decode(&line, &mut lines);
+            start = 0;
+        }
+
+        if self.pending.len() > MAX_LINE_LENGTH {
+            // Never split inside of a character, the rest of it is still to be read
+            let boundary = match std::str::from_utf8(&self.pending) {
+                Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                _ => self.pending.len(),
+            };
+            let rest = self.pending.split_off(boundary);
+            let line = std::mem::replace(&mut self.pending, rest);
+            self.decode(&line, &mut lines);
+        }
+        lines
+    }
+
+    /// Returns what is left once the stream ended, like a last line without a line break
+    pub fn finish(&mut self) -> Vec<OutputLine> {
+        let mut lines = Vec::new();
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.decode(&line, &mut lines);
+        }
+
+        // An event which was never closed is shown as it was written
+        if let Some(event) = self.event.take() {
+            for text in event.lines() {
+                lines.push(self.line(text.to_string()));
+            }
+        }
+        lines
+    }
+
+    fn decode(&mut self, bytes: &[u8], lines: &mut Vec<OutputLine>) {
+        // Invalid bytes, e.g. of a process writing in the code page of the system, are replaced instead of dropped
+        let text = String::from_utf8_lossy(bytes)
+            .trim_end_matches(['\r', '\n'])
+            .to_string();
+
+        // XML log events of the Log4j configurations of Mojang and the launcher span multiple lines
+        if self.event.is_some() || text.trim_start().starts_with("<log4j:Event") {
+            let event = self.event.get_or_insert_with(String::new);
+            event.push_str(&text);
+            event.push('\n');
+
+            if text.contains("</log4j:Event>") || event.len() > MAX_EVENT_LENGTH {
+                let event = self.event.take().unwrap_or_default();
+                self.decode_event(&event, lines);
+            }
+            return;
+        }
+
+        if let Some(captures) = LOG4J_PREFIX.captures(&text) {
+            self.level = LogLevel::parse(&captures[2]);
+            self.thread = Some(captures[1].to_string());
+            self.logger = captures.get(3).map(|logger| logger.as_str().to_string());
+        } else if text.is_empty() {
+            // Blank lines end a stack trace
+            self.level = None;
+            self.thread = None;
+            self.logger = None;
+        }
+
+        lines.push(self.line(text));
+    }
+
+    ///
+    /// Turns a `<log4j:Event>` into lines in the format of the Minecraft console,
+    /// the message and the stack trace of the event are split into one line each
+    ///
+    fn decode_event(&mut self, event: &str, lines: &mut Vec<OutputLine>) {
+        let tag = &event[..event.find('>').unwrap_or(event.len())];
+        let attribute = |name: &str| {
+            EVENT_ATTRIBUTE
+                .captures_iter(tag)
+                .find(|captures| &captures[1] == name)
+                .map(|captures| unescape(&captures[2]))
+        };
+
+        let Some(level) = attribute("level").and_then(|level| LogLevel::parse(&level)) else {
+            // Not an event after all, e.g. an unrelated line starting with the tag
+            for text in event.lines() {
+                lines.push(self.line(text.to_string()));
+            }
+            return;
+        };
+
+        self.level = Some(level);
+        self.thread = attribute("thread");
+        self.logger = attribute("logger");
+
+        let time = attribute("timestamp")
+            .and_then(|timestamp| timestamp.parse::<i64>().ok())
+            .and_then(|timestamp| Local.timestamp_millis_opt(timestamp).single())
+            .unwrap_or_else(Local::now)
+            .format("%H:%M:%S");
+        let prefix = format!(
+            "[{}] [{}/{}]: ",
+            time,
+            self.thread.as_deref().unwrap_or_default(),
+            level.name()
+        );
+
+        let message = element(event, "log4j:Message").unwrap_or_default();
+        let throwable = element(event, "log4j:Throwable").unwrap_or_default();
+
+        let mut texts = message.lines().chain(throwable.lines());
+        let first = texts.next().unwrap_or_default();
+        lines.push(self.line(format!("{}{}", prefix, first)));
+        for text in texts {
+            lines.push(self.line(text.to_string()));
+        }
+    }
+
+    fn line(&self, text: String) -> OutputLine {
+        OutputLine {
+            stream: self.stream,
+            timestamp: timestamp(),
+            level: self.level,
+            thread: self.thread.clone(),
+            logger: self.logger.clone(),
+            text,
+        }
+    }
+}
+
+/// Content of the element `name` of an event, without the CDATA sections around it
+fn element(event: &str, name: &str) -> Option<String> {
+    let open = format!("<{}>", name);
+    let start = event.find(&open)? + open.len();
+    let end = start + event[start..].find(&format!("</{}>", name))?;
+    let content = event[start..end].trim();
+
+    // A CDATA section containing `]]>` is split into `]]]]><![CDATA[>`
+    Some(match content.strip_prefix("<![CDATA[").and_then(|content| content.strip_suffix("]]>")) {
+        Some(content) => content.replace("]]><![CDATA[", ""),
+        None => unescape(content),
+    })
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[OutputLine]) -> Vec<&str> {
+        lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn strips_crlf_line_endings() {
+        let mut decoder = LineDecoder::new(OutputStream::Stdout);
+        let lines = decoder.push(b"first\r\nsecond\r\n");
+
+        assert_eq!(texts(&lines), ["first", "second"]);
+        assert!(decoder.finish().is_empty());
+    }
+
+    #[test]
+    fn replaces_invalid_utf8() {
+        let mut decoder = LineDecoder::new(OutputStream::Stderr);
+        let lines = decoder.push(b"a\xffb\n");
+
+        assert_eq!(texts(&lines), ["a\u{FFFD}b"]);
+        assert_eq!(lines[0].stream, OutputStream::Stderr);
+    }
+
+    #[test]
+    fn joins_lines_and_characters_split_across_reads() {
+        let mut decoder = LineDecoder::new(OutputStream::Stdout);
+
+        assert!(decoder.push(b"hello w").is_empty());
+        // `ä` is 0xC3 0xA4 and split between two reads
+        assert!(decoder.push(b"orld caf\xc3").is_empty());
+        let lines = decoder.push(b"\xa4\nrest");
+        assert_eq!(texts(&lines), ["hello world caf\u{e4}"]);
+
+        assert_eq!(texts(&decoder.finish()), ["rest"]);
+    }
+
+    #[test]
+    fn splits_overlong_lines_at_character_boundaries() {
+        let mut decoder = LineDecoder::new(OutputStream::Stdout);
+        let mut bytes = vec![b'a'; MAX_LINE_LENGTH];
+        bytes.push(0xC3);
+
+        let lines = decoder.push(&bytes);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].text.len(), MAX_LINE_LENGTH);
+
+        assert_eq!(texts(&decoder.push(b"\xa4\n")), ["\u{e4}"]);
+    }
+
+    #[test]
+    fn detects_log4j_prefixes() {
+        let mut decoder = LineDecoder::new(OutputStream::Stdout);
+        let lines = decoder.push(
+            b"[12:34:56] [Render thread/WARN]: Something is off\n\
+            \tat net.minecraft.client.Main.main(Main.java:1)\n\
+            \n\
+            [12:34:57] [main/INFO] (Minecraft) Loading\n\
+            plain output\n",
+        );
+
+        assert_eq!(lines[0].level, Some(LogLevel::Warn));
+        assert_eq!(lines[0].thread.as_deref(), Some("Render thread"));
+        assert_eq!(lines[0].logger, None);
+
+        // Stack traces continue the line before, blank lines end them
+        assert_eq!(lines[1].level, Some(LogLevel::Warn));
+        assert_eq!(lines[2].level, None);
+
+        assert_eq!(lines[3].level, Some(LogLevel::Info));
+        assert_eq!(lines[3].thread.as_deref(), Some("main"));
+        assert_eq!(lines[3].logger.as_deref(), Some("Minecraft"));
+        assert_eq!(lines[4].level, Some(LogLevel::Info));
+    }
+
+    #[test]
+    fn decodes_log4j_xml_events() {
+        let mut decoder = LineDecoder::new(OutputStream::Stdout);
+        let lines = decoder.push(
+            b"<log4j:Event logger=\"net.minecraft.client.Minecraft\" timestamp=\"1700000000000\" level=\"ERROR\" thread=\"Render thread\">\n\
+            \x20 <log4j:Message><![CDATA[Something <broke>]]></log4j:Message>\n\
+            \x20 <log4j:Throwable><![CDATA[java.lang.IllegalStateException: broken\n\
+            \tat Foo.bar(Foo.java:1)\n\
+            ]]></log4j:Throwable>\n\
+            </log4j:Event>\n\
+            after\n",
+        );
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].text.ends_with("[Render thread/ERROR]: Something <broke>"));
+        assert_eq!(lines[0].level, Some(LogLevel::Error));
+        assert_eq!(lines[0].thread.as_deref(), Some("Render thread"));
+        assert_eq!(lines[0].logger.as_deref(), Some("net.minecraft.client.Minecraft"));
+        assert_eq!(lines[1].text, "java.lang.IllegalStateException: broken");
+        assert_eq!(lines[2].text, "\tat Foo.bar(Foo.java:1)");
+        assert_eq!(lines[2].level, Some(LogLevel::Error));
+        assert_eq!(lines[3].text, "after");
+    }
+
+    #[test]
+    fn shows_unclosed_xml_events_as_written() {
+        let mut decoder = LineDecoder::new(OutputStream::Stdout);
+
+        assert!(decoder.push(b"<log4j:Event level=\"INFO\">\n").is_empty());
+        assert_eq!(texts(&decoder.finish()), ["<log4j:Event level=\"INFO\">"]);
+    }
+}
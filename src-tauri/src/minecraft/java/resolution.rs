@@ -0,0 +1,142 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::iter::once;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use tracing::{debug, info};
+
+use crate::utils::{Architecture, OperatingSystem, ARCHITECTURE, OS};
+
+use super::{JavaDistribution, JreDownload};
+
+/// Distributions which are tried when the selected one has no build for the system, in this order
+const FALLBACK_ORDER: [JavaDistribution; 6] = [
+    JavaDistribution::Temurin,
+    JavaDistribution::Zulu,
+    JavaDistribution::Liberica,
+    JavaDistribution::Corretto,
+    JavaDistribution::Microsoft,
+    JavaDistribution::SapMachine,
+];
+
+/// Installed together with Rosetta 2, which runs x64 binaries on Apple Silicon
+const ROSETTA_RUNTIME: &str = "/Library/Apple/usr/libexec/oah/libRosettaRuntime";
+
+/// The JRE picked by [resolve_download] and why it was picked
+pub struct JreResolution {
+    pub distribution: JavaDistribution,
+    pub download: JreDownload,
+    pub reason: String,
+}
+
+/// The selected distribution followed by the fallbacks, all supporting the Java version
+pub fn candidate_distributions(preferred: &JavaDistribution, jre_version: u32) -> Vec<JavaDistribution> {
+    once(preferred.clone())
+        .chain(FALLBACK_ORDER.iter().filter(|distribution| *distribution != preferred).cloned())
+        .filter(|distribution| distribution.supports_version(jre_version))
+        .collect()
+}
+
+/// Architectures the system can run besides its own, and how
+fn emulated_architectures() -> Vec<(Architecture, &'static str)> {
+    match (&OS, &*ARCHITECTURE) {
+        (OperatingSystem::OSX, Architecture::AARCH64) if Path::new(ROSETTA_RUNTIME).exists() => {
+            vec![(Architecture::X64, "Rosetta 2")]
+        }
+        (OperatingSystem::WINDOWS, Architecture::AARCH64) => vec![
+            (Architecture::X64, "Windows x64 emulation"),
+            (Architecture::X86, "Windows x86 emulation"),
+        ],
+        (OperatingSystem::WINDOWS, Architecture::X64) => vec![(Architecture::X86, "WOW64")],
+        _ => vec![],
+    }
+}
+
+fn arch_name(arch: &Architecture) -> &'static str {
+    arch.get_simple_name().unwrap_or("unknown")
+}
+
+///
+/// Picks the JRE to download for the required Java version.
+///
+/// The selected distribution is used if it has a native build for the system. Otherwise the
+/// other distributions are asked for a native build, and only if none has one, an x64 or x86 build
+/// is used through emulation (e.g. Rosetta 2 on Apple Silicon).
+///
+pub async fn resolve_download(preferred: &JavaDistribution, jre_version: u32) -> Result<JreResolution> {
+    let native = ARCHITECTURE.clone();
+    let candidates = candidate_distributions(preferred, jre_version);
+    let mut unavailable = Vec::new();
+
+    if !preferred.supports_version(jre_version) {
+        unavailable.push(format!("{} does not support Java {}", preferred.get_name(), jre_version));
+    }
+
+    let architectures = once((native.clone(), "")).chain(emulated_architectures());
+    for (arch, emulation) in architectures {
+        for distribution in &candidates {
+            let download = match distribution.get_download_for(&jre_version, &arch).await {
+                Ok(download) => download,
+                Err(e) => {
+                    debug!("{} has no Java {} for {}: {:?}", distribution.get_name(), jre_version, arch_name(&arch), e);
+                    unavailable.push(format!(
+                        "{} has no Java {} build for {}: {:#}",
+                        distribution.get_name(),
+                        jre_version,
+                        arch_name(&arch),
+                        e
+                    ));
+                    continue;
+                }
+            };
+
+            let reason = if arch != native {
+                format!(
+                    "No distribution has a native Java {} build for {}, using the {} build of {} through {}",
+                    jre_version,
+                    arch_name(&native),
+                    arch_name(&arch),
+                    distribution.get_name(),
+                    emulation
+                )
+            } else if distribution != preferred {
+                format!("{}, using {} instead", unavailable[0], distribution.get_name())
+            } else {
+                format!("Using native {} build of {}", arch_name(&arch), distribution.get_name())
+            };
+            info!("{}", reason);
+
+            return Ok(JreResolution {
+                distribution: distribution.clone(),
+                download,
+                reason,
+            });
+        }
+    }
+
+    bail!(
+        "No Java {} runtime is available for {} {}:\n{}",
+        jre_version,
+        OS,
+        arch_name(&native),
+        unavailable.join("\n")
+    )
+}
@@ -18,12 +18,27 @@
  */
 
 use anyhow::{bail, Result};
+use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
 use tokio::io::AsyncReadExt;
 use tokio::process::{Child, Command};
 use tokio::sync::oneshot::Receiver;
-use tracing::debug;
+use tracing::{debug, warn};
+
+use crate::minecraft::java::output::{LineDecoder, OutputStream};
+use crate::minecraft::launcher::LauncherFrontend;
+use crate::minecraft::session::SessionExit;
+use crate::minecraft::session_log::SessionLog;
+
+/// How long output left in the pipes is read after the process exited
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Lines of stderr kept for the crash analysis, in case the game crashed without writing a crash report
+const STDERR_TAIL: usize = 200;
+
 pub struct JavaRuntime(PathBuf);
 
 impl JavaRuntime {
@@ -48,45 +63,119 @@ impl JavaRuntime {
         Ok(child)
     }
 
-    pub async fn handle_io<D: Send + Sync>(
+    pub async fn handle_io<D: LauncherFrontend>(
         &self,
         running_task: &mut Child,
-        on_stdout: fn(&D, &[u8]) -> Result<()>,
-        on_stderr: fn(&D, &[u8]) -> Result<()>,
         terminator: Receiver<()>,
         data: &D,
-    ) -> Result<()> {
+        log: Option<SessionLog>,
+    ) -> Result<SessionExit> {
         let mut stdout = running_task.stdout.take().unwrap();
         let mut stderr = running_task.stderr.take().unwrap();
 
-        let mut stdout_buf = vec![0; 1024];
-        let mut stderr_buf = vec![0; 1024];
+        let mut stdout_buf = vec![0; 8192];
+        let mut stderr_buf = vec![0; 8192];
+        let mut stdout_decoder = LineDecoder::new(OutputStream::Stdout);
+        let mut stderr_decoder = LineDecoder::new(OutputStream::Stderr);
+        let mut stdout_open = true;
+        let mut stderr_open = true;
+        // Shared by both streams, which are drained at the same time
+        let log = Mutex::new(log);
+        let mut tail = VecDeque::with_capacity(STDERR_TAIL);
 
         tokio::pin!(terminator);
 
-        loop {
+        let exit_status = loop {
             tokio::select! {
-                read_len = stdout.read(&mut stdout_buf) => {
-                    let _ = on_stdout(&data, &stdout_buf[..read_len?]);
+                read_len = stdout.read(&mut stdout_buf), if stdout_open => {
+                    stdout_open = forward(&mut stdout_decoder, &stdout_buf, read_len?, data, &log, None);
                 },
-                read_len = stderr.read(&mut stderr_buf) => {
-                    let _ = on_stderr(&data, &stderr_buf[..read_len?]);
+                read_len = stderr.read(&mut stderr_buf), if stderr_open => {
+                    stderr_open = forward(&mut stderr_decoder, &stderr_buf, read_len?, data, &log, Some(&mut tail));
                 },
                 _ = &mut terminator => {
                     running_task.kill().await?;
-                    break;
+
+                    // Lines which were not finished by the game are still shown
+                    forward(&mut stdout_decoder, &[], 0, data, &log, None);
+                    forward(&mut stderr_decoder, &[], 0, data, &log, None);
+                    return Ok(SessionExit { code: None, signal: None, terminated: true, stderr_tail: vec![] });
                 },
                 exit_status = running_task.wait() => {
-                    let code = exit_status?.code().unwrap_or(7900); // 7900 = unwrap failed error code
+                    let exit_status = exit_status?;
 
-                    debug!("Process exited with code: {}", code);
-                    if code != 0 && code != -1073740791 { // -1073740791 = happens when the process is killed forcefully, we don't want to bail in this case
-                        bail!("Process exited with non-zero exit code: {}.", code);
-                    }
-                    break;
+                    debug!("Process exited with {}", exit_status);
+                    break exit_status;
                 },
             }
+        };
+
+        // The pipes may still hold output written right before the exit, like the cause of a crash.
+        // Processes started by the game can keep them open, so this is not waited for indefinitely.
+        // Both are drained at the same time, so a stream which is kept open doesn't hold back the other one.
+        let drain_stdout = async {
+            while stdout_open {
+                let read_len = stdout.read(&mut stdout_buf).await.unwrap_or_default();
+                stdout_open = forward(&mut stdout_decoder, &stdout_buf, read_len, data, &log, None);
+            }
+        };
+        let drain_stderr = async {
+            while stderr_open {
+                let read_len = stderr.read(&mut stderr_buf).await.unwrap_or_default();
+                stderr_open = forward(&mut stderr_decoder, &stderr_buf, read_len, data, &log, Some(&mut tail));
+            }
+        };
+        let _ = tokio::time::timeout(DRAIN_TIMEOUT, async { tokio::join!(drain_stdout, drain_stderr) }).await;
+
+        // Lines which were not finished when the timeout hit
+        forward(&mut stdout_decoder, &[], 0, data, &log, None);
+        forward(&mut stderr_decoder, &[], 0, data, &log, Some(&mut tail));
+
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&exit_status);
+        #[cfg(not(unix))]
+        let signal = None;
+
+        Ok(SessionExit {
+            code: exit_status.code(),
+            signal,
+            terminated: false,
+            stderr_tail: tail.into(),
+        })
+    }
+}
+
+/// Passes the complete lines of what was read on to the frontend, the session log and the tail if given,
+/// returns false once the stream is closed
+fn forward<D: LauncherFrontend>(
+    decoder: &mut LineDecoder,
+    buf: &[u8],
+    read_len: usize,
+    data: &D,
+    log: &Mutex<Option<SessionLog>>,
+    mut tail: Option<&mut VecDeque<String>>,
+) -> bool {
+    let lines = if read_len == 0 {
+        decoder.finish()
+    } else {
+        decoder.push(&buf[..read_len])
+    };
+
+    for line in lines {
+        if let Ok(mut log) = log.lock() {
+            if let Some(Err(e)) = log.as_mut().map(|session_log| session_log.write(&line)) {
+                // The game keeps running, only the log file is given up on
+                warn!("Failed to write session log: {:?}", e);
+                *log = None;
+            }
+        }
+        if let Some(tail) = tail.as_deref_mut() {
+            if tail.len() == STDERR_TAIL {
+                tail.pop_front();
+            }
+            tail.push_back(line.text.clone());
         }
-        Ok(())
+        let _ = data.on_output(&line);
     }
+    read_len != 0
 }
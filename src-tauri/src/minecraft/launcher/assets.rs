@@ -1,4 +1,5 @@
 use std::{
+    collections::HashSet,
     path::{Path, PathBuf},
     sync::{
         atomic::{AtomicU64, Ordering},
@@ -20,9 +21,9 @@ use crate::{
     },
 };
 
-use super::{LauncherData, StartParameter};
+use super::{LauncherData, LauncherFrontend, StartParameter};
 
-pub async fn setup_assets<'a, D: Send + Sync>(
+pub async fn setup_assets<'a, D: LauncherFrontend>(
     assets_folder: &'a Path,
     version_profile: &'a VersionProfile,
     launching_parameter: &'a StartParameter,
@@ -40,9 +41,12 @@ pub async fn setup_assets<'a, D: Send + Sync>(
     let asset_index = asset_index_location
         .load_asset_index(&indexes_folder)
         .await?;
+    // Objects are stored by hash, identical files listed under several names are downloaded once
+    let mut hashes = HashSet::new();
     let asset_objects_to_download = asset_index
         .objects
         .values()
+        .filter(|x| hashes.insert(x.hash.as_str()))
         .map(|x| x.to_owned())
         .collect::<Vec<_>>();
     let assets_downloaded = Arc::new(AtomicU64::new(0));
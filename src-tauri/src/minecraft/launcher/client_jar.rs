@@ -13,9 +13,10 @@ use crate::{
     utils::{download_file, sha1sum, OS},
 };
 
-use super::LauncherData;
+use super::plan::PlannedArtifact;
+use super::{LauncherData, LauncherFrontend};
 
-pub async fn setup_client_jar<D: Send + Sync>(
+pub async fn setup_client_jar<D: LauncherFrontend>(
     client_folder: &Path,
     natives_folder: &Path,
     version_profile: &VersionProfile,
@@ -41,7 +42,7 @@ pub async fn setup_client_jar<D: Send + Sync>(
         let requires_download = if !client_jar.exists() {
             true
         } else {
-            let hash = sha1sum(&client_jar)?;
+            let hash = sha1sum(&client_jar).await?;
             launcher_data.log(&*format!(
                 "Client JAR local hash: {}, remote: {}",
                 hash, client_download.sha1
@@ -53,21 +54,18 @@ pub async fn setup_client_jar<D: Send + Sync>(
             launcher_data.log("Downloading client...");
             launcher_data.progress_update(ProgressUpdate::set_label("Downloading client..."));
 
-            let retrieved_bytes = download_file(&client_download.url, |a, b| {
+            // SHA1 is computed while downloading
+            let hash = download_file(&client_download.url, &client_jar, |a, b| {
                 launcher_data.progress_update(ProgressUpdate::set_for_step(
                     ProgressUpdateSteps::DownloadClientJar,
                     get_progress(0, a, b),
                     get_max(1),
                 ));
             })
-            .await?;
-
-            fs::write(&client_jar, retrieved_bytes)
-                .await
-                .context("Failed to write client JAR")?;
+            .await
+            .context("Failed to download client JAR")?;
 
             // After downloading, check sha1
-            let hash = sha1sum(&client_jar)?;
             launcher_data.log(&*format!(
                 "Client JAR local hash: {}, remote: {}",
                 hash, client_download.sha1
@@ -92,3 +90,23 @@ pub async fn setup_client_jar<D: Send + Sync>(
 
     Ok(())
 }
+
+/// Returns the client JAR of the version profile, without downloading it
+pub fn resolve_client_jar(client_folder: &Path, version_profile: &VersionProfile) -> Result<PlannedArtifact> {
+    let client_download = version_profile
+        .downloads
+        .as_ref()
+        .and_then(|x| x.client.as_ref())
+        .ok_or_else(|| {
+            LauncherError::InvalidVersionProfile("No client JAR downloads were specified.".to_string())
+        })?;
+    let path = client_folder.join(format!("{}.jar", &version_profile.id));
+
+    Ok(PlannedArtifact {
+        name: version_profile.id.clone(),
+        present: path.exists(),
+        path,
+        url: client_download.url.clone(),
+        sha1: Some(client_download.sha1.clone()),
+    })
+}
@@ -0,0 +1,270 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::minecraft::cache::BuildCache;
+use crate::minecraft::java::{candidate_distributions, find_java_binary, DistributionSelection};
+use crate::minecraft::version::AssetIndex;
+use crate::utils::{disk_usage, is_path_in_use};
+
+use super::libraries::resolve_libraries;
+
+///
+/// Files which are not used by any cached build, see [collect_garbage]
+///
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GarbageReport {
+    pub dry_run: bool,
+    pub libraries: Reclaimable,
+    pub assets: Reclaimable,
+    pub versions: Reclaimable,
+    pub runtimes: Reclaimable,
+}
+
+impl GarbageReport {
+    pub fn total_bytes(&self) -> u64 {
+        self.libraries.bytes + self.assets.bytes + self.versions.bytes + self.runtimes.bytes
+    }
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Reclaimable {
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+/// Everything the cached builds need from the shared folders of the data directory
+#[derive(Default)]
+struct Referenced {
+    versions: HashSet<String>,
+    libraries: HashSet<PathBuf>,
+    asset_indexes: HashSet<String>,
+    asset_objects: HashSet<String>,
+    /// Set if an asset index is missing, in which case the used objects are unknown
+    all_asset_objects: bool,
+    runtimes: HashSet<String>,
+}
+
+///
+/// Deletes the libraries, asset objects, versions and runtimes which none of the cached builds use.
+///
+/// The runtimes of every distribution in [java_distributions] are kept, these are the
+/// distributions selected in the launcher and in each instance.
+/// With [dry_run] nothing is deleted, the report only tells how much space would be reclaimed.
+///
+pub async fn collect_garbage(
+    data: &Path,
+    java_distributions: &[DistributionSelection],
+    dry_run: bool,
+) -> Result<GarbageReport> {
+    // Also catches games which were not started by this process, e.g. from the command line
+    if !dry_run && is_path_in_use(data) {
+        bail!("{} is in use by a running game", data.display());
+    }
+
+    let referenced = referenced_files(data, java_distributions).await?;
+    let data = data.to_path_buf();
+
+    tokio::task::spawn_blocking(move || sweep(&data, &referenced, dry_run)).await?
+}
+
+async fn referenced_files(data: &Path, java_distributions: &[DistributionSelection]) -> Result<Referenced> {
+    let builds = BuildCache::cached_builds(data).await?;
+    if builds.is_empty() {
+        bail!("No build has been launched yet, unable to tell which files are in use");
+    }
+
+    let features: HashSet<String> = HashSet::new();
+    let libraries_folder = data.join("libraries");
+    let indexes_folder = data.join("assets").join("indexes");
+    let mut referenced = Referenced::default();
+
+    for build in builds {
+        // Runtimes are named like in the JRE downloader
+        referenced.runtimes.insert(format!(
+            "{}_{}",
+            build.jre_distribution.get_name(),
+            build.jre_version
+        ));
+        for java_distribution in java_distributions {
+            let selected = match java_distribution {
+                DistributionSelection::Manual(distribution) => distribution,
+                _ => &build.jre_distribution,
+            };
+            referenced
+                .runtimes
+                .insert(format!("{}_{}", selected.get_name(), build.jre_version));
+
+            // The fallback which is launched if the selected distribution has no build for this system
+            for fallback in candidate_distributions(selected, build.jre_version) {
+                if find_java_binary(&data.join("runtimes"), &fallback, &build.jre_version).await.is_ok() {
+                    referenced
+                        .runtimes
+                        .insert(format!("{}_{}", fallback.get_name(), build.jre_version));
+                    break;
+                }
+            }
+        }
+
+        // The profile is cached before anything is downloaded, a build without it has no files yet
+        let Ok(profile) = BuildCache::new(data, build.build_id).load_version_profile().await else {
+            warn!("Build {} has no cached version profile", build.build_id);
+            continue;
+        };
+
+        let (libraries, natives) = resolve_libraries(&libraries_folder, &profile, &features)?;
+        for artifact in libraries.into_iter().chain(natives) {
+            referenced.libraries.insert(artifact.path.with_extension("sha1"));
+            referenced.libraries.insert(artifact.path);
+        }
+
+        if let Some(location) = &profile.asset_index_location {
+            let index_name = format!("{}.json", location.id);
+            let asset_index = tokio::fs::read(indexes_folder.join(&index_name))
+                .await
+                .ok()
+                .and_then(|content| serde_json::from_slice::<AssetIndex>(&content).ok());
+
+            match asset_index {
+                Some(asset_index) => referenced
+                    .asset_objects
+                    .extend(asset_index.objects.into_values().map(|object| object.hash)),
+                None => referenced.all_asset_objects = true,
+            }
+            referenced.asset_indexes.insert(index_name);
+        }
+
+        referenced.versions.insert(profile.id);
+    }
+
+    Ok(referenced)
+}
+
+fn sweep(data: &Path, referenced: &Referenced, dry_run: bool) -> Result<GarbageReport> {
+    let libraries_folder = data.join("libraries");
+    let assets_folder = data.join("assets");
+
+    let mut libraries = vec![];
+    files_below(&libraries_folder, &mut libraries);
+    libraries.retain(|path| !referenced.libraries.contains(path));
+
+    let mut assets = vec![];
+    if !referenced.all_asset_objects {
+        files_below(&assets_folder.join("objects"), &mut assets);
+        assets.retain(|path| !is_referenced(path, &referenced.asset_objects));
+    }
+    assets.extend(
+        entries_of(&assets_folder.join("indexes"))
+            .into_iter()
+            .filter(|path| !is_referenced(path, &referenced.asset_indexes)),
+    );
+
+    let versions = entries_of(&data.join("versions"))
+        .into_iter()
+        .filter(|path| !is_referenced(path, &referenced.versions))
+        .collect();
+
+    // Also catches archives which were left behind by an interrupted JRE download,
+    // the install locks are kept since another process might be holding one
+    let runtimes = entries_of(&data.join("runtimes"))
+        .into_iter()
+        .filter(|path| !is_referenced(path, &referenced.runtimes))
+        .filter(|path| path.extension().is_none_or(|extension| extension != "lock"))
+        .collect();
+
+    let report = GarbageReport {
+        dry_run,
+        libraries: remove_all(libraries, dry_run)?,
+        assets: remove_all(assets, dry_run)?,
+        versions: remove_all(versions, dry_run)?,
+        runtimes: remove_all(runtimes, dry_run)?,
+    };
+
+    if !dry_run {
+        remove_empty_dirs(&libraries_folder);
+        remove_empty_dirs(&assets_folder.join("objects"));
+        info!("Reclaimed {} bytes", report.total_bytes());
+    }
+    Ok(report)
+}
+
+fn is_referenced(path: &Path, names: &HashSet<String>) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| names.contains(name))
+}
+
+/// Returns the direct children of the directory
+fn entries_of(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+/// Collects the files in the directory and all of its subdirectories
+fn files_below(dir: &Path, files: &mut Vec<PathBuf>) {
+    for path in entries_of(dir) {
+        if path.is_dir() {
+            files_below(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+fn remove_all(paths: Vec<PathBuf>, dry_run: bool) -> Result<Reclaimable> {
+    let mut reclaimable = Reclaimable::default();
+
+    for path in paths {
+        reclaimable.entries += 1;
+        reclaimable.bytes += disk_usage(&path);
+
+        if dry_run {
+            continue;
+        }
+
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        }
+        .with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+
+    Ok(reclaimable)
+}
+
+/// Removes the directories below which became empty, keeps the directory itself
+fn remove_empty_dirs(dir: &Path) {
+    for path in entries_of(dir) {
+        if path.is_dir() {
+            remove_empty_dirs(&path);
+            // Fails if there is anything left in it
+            let _ = std::fs::remove_dir(&path);
+        }
+    }
+}
@@ -1,16 +1,22 @@
 use std::path::{Path, PathBuf};
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 
-use super::{LauncherData, StartParameter};
+use super::plan::PlannedJava;
+use super::{LauncherData, LauncherFrontend, StartParameter};
 use crate::app::client_api::LaunchManifest;
+use crate::error::LauncherError;
 use crate::minecraft::java::DistributionSelection;
 use crate::minecraft::{
-    java::{find_java_binary, jre_downloader},
+    java::{
+        candidate_distributions, find_installed_java, find_java_binary, jre_downloader, probe_java,
+        resolve_download,
+    },
     progress::{get_max, get_progress, ProgressReceiver, ProgressUpdate, ProgressUpdateSteps},
 };
+use crate::utils::ARCHITECTURE;
 
-pub async fn load_jre<D: Send + Sync>(
+pub async fn load_jre<D: LauncherFrontend>(
     runtimes_folder: &Path,
     manifest: &LaunchManifest,
     launching_parameter: &StartParameter,
@@ -18,31 +24,90 @@ pub async fn load_jre<D: Send + Sync>(
 ) -> Result<PathBuf> {
     let distribution = match &launching_parameter.java_distribution {
         DistributionSelection::Automatic(_) => &manifest.build.jre_distribution,
+        // Already checked by the prelauncher, see [check_custom_java]
         DistributionSelection::Custom(path) => return Ok(PathBuf::from(path)),
         DistributionSelection::Manual(distribution) => distribution,
     };
 
-    // Check if distribution supports JRE version
-    if !distribution.supports_version(manifest.build.jre_version) {
-        return Err(anyhow!(
-            "The selected JRE distribution does not support the required version of Java."
-        ));
-    }
-
     launcher_data.progress_update(ProgressUpdate::set_label("Checking for JRE..."));
 
     if let Ok(path) =
         find_java_binary(runtimes_folder, distribution, &manifest.build.jre_version).await
     {
-        return Ok(path);
+        if launching_parameter.offline_mode {
+            return Ok(path);
+        }
+
+        launcher_data.progress_update(ProgressUpdate::set_label("Checking for JRE updates..."));
+        return match jre_downloader::jre_update(
+            runtimes_folder,
+            distribution,
+            &manifest.build.jre_version,
+            |a, b| {
+                launcher_data.progress_update(ProgressUpdate::set_for_step(
+                    ProgressUpdateSteps::DownloadJRE,
+                    get_progress(0, a, b),
+                    get_max(1),
+                ));
+            },
+        )
+        .await
+        {
+            Ok(Some(updated)) => {
+                launcher_data.log("Updated JRE to the latest release");
+                Ok(updated)
+            }
+            Ok(None) => Ok(path),
+            Err(e) => {
+                // The installed runtime still works, the update is tried again on the next launch
+                launcher_data.log(&format!("Failed to update JRE: {:#}", e));
+                Ok(path)
+            }
+        };
+    }
+
+    // Only an automatic selection may fall back to a runtime installed on the system
+    if matches!(launching_parameter.java_distribution, DistributionSelection::Automatic(_)) {
+        if let Some(java) = find_installed_java(manifest.build.jre_version).await {
+            launcher_data.log(&format!(
+                "Using installed Java {} at {}",
+                java.version,
+                java.path.display()
+            ));
+            return Ok(java.path);
+        }
+    }
+
+    // The selected distribution might have no build for this system, e.g. on arm or 32-bit x86
+    let resolution = match resolve_download(distribution, manifest.build.jre_version).await {
+        Ok(resolution) => resolution,
+        Err(e) => {
+            // Offline, a fallback installed by an earlier launch is still good to use
+            for fallback in candidate_distributions(distribution, manifest.build.jre_version) {
+                if let Ok(path) = find_java_binary(runtimes_folder, &fallback, &manifest.build.jre_version).await {
+                    launcher_data.log(&format!("Using installed {} JRE", fallback.get_name()));
+                    return Ok(path);
+                }
+            }
+            return Err(e);
+        }
+    };
+    launcher_data.log(&resolution.reason);
+
+    if resolution.distribution != *distribution {
+        if let Ok(path) =
+            find_java_binary(runtimes_folder, &resolution.distribution, &manifest.build.jre_version).await
+        {
+            return Ok(path);
+        }
     }
 
     launcher_data.log("Downloading JRE...");
     launcher_data.progress_update(ProgressUpdate::set_label("Download JRE..."));
 
-    jre_downloader::jre_download(
+    jre_downloader::jre_install(
         &runtimes_folder,
-        distribution,
+        &resolution,
         &manifest.build.jre_version,
         |a, b| {
             launcher_data.progress_update(ProgressUpdate::set_for_step(
@@ -54,3 +119,89 @@ pub async fn load_jre<D: Send + Sync>(
     )
     .await
 }
+
+///
+/// Runs the custom Java to make sure it matches the required Java version and the system architecture,
+/// instead of failing with a JVM crash once everything has been downloaded.
+///
+pub async fn check_custom_java(path: &Path, jre_version: u32) -> Result<(), LauncherError> {
+    let java = probe_java(path)
+        .await
+        .map_err(|e| LauncherError::InvalidCustomJava {
+            path: path.display().to_string(),
+            reason: format!("{:#}", e),
+        })?;
+
+    if java.major_version != jre_version {
+        return Err(LauncherError::IncompatibleJavaVersion {
+            path: path.display().to_string(),
+            found: java.major_version,
+            required: jre_version,
+        });
+    }
+
+    if java.architecture != *ARCHITECTURE {
+        return Err(LauncherError::IncompatibleJavaArchitecture {
+            path: path.display().to_string(),
+            found: java.architecture.get_simple_name().unwrap_or("an unknown architecture").to_string(),
+            required: ARCHITECTURE.get_simple_name().unwrap_or("an unknown architecture").to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks which JRE would be used, without downloading it
+pub async fn resolve_jre(
+    runtimes_folder: &Path,
+    manifest: &LaunchManifest,
+    launching_parameter: &StartParameter,
+) -> Result<PlannedJava> {
+    let version = manifest.build.jre_version;
+    let distribution = match &launching_parameter.java_distribution {
+        DistributionSelection::Automatic(_) => &manifest.build.jre_distribution,
+        DistributionSelection::Custom(path) => {
+            return Ok(PlannedJava {
+                distribution: "custom".to_string(),
+                version,
+                binary: Some(PathBuf::from(path)),
+                download_required: false,
+            })
+        }
+        DistributionSelection::Manual(distribution) => distribution,
+    };
+
+    let binary = find_java_binary(runtimes_folder, distribution, &version).await.ok();
+
+    if binary.is_none() && matches!(launching_parameter.java_distribution, DistributionSelection::Automatic(_)) {
+        if let Some(java) = find_installed_java(version).await {
+            return Ok(PlannedJava {
+                distribution: "system".to_string(),
+                version,
+                binary: Some(java.path),
+                download_required: false,
+            });
+        }
+    }
+
+    // A fallback picked by an earlier launch, which download is picked otherwise is only known at launch
+    if binary.is_none() {
+        for fallback in candidate_distributions(distribution, version) {
+            if let Ok(path) = find_java_binary(runtimes_folder, &fallback, &version).await {
+                return Ok(PlannedJava {
+                    distribution: fallback.get_name().to_string(),
+                    version,
+                    binary: Some(path),
+                    download_required: false,
+                });
+            }
+        }
+    }
+
+    Ok(PlannedJava {
+        distribution: distribution.get_name().to_string(),
+        version,
+        download_required: binary.is_none(),
+        binary,
+    })
+}
@@ -17,9 +17,10 @@ use crate::{
     utils::{zip_extract, OS},
 };
 
-use super::{LauncherData, StartParameter};
+use super::plan::PlannedArtifact;
+use super::{LauncherData, LauncherFrontend, StartParameter};
 
-pub async fn setup_libraries<D: Send + Sync>(
+pub async fn setup_libraries<D: LauncherFrontend>(
     libraries_folder: &Path,
     natives_folder: &Path,
     version_profile: &VersionProfile,
@@ -146,3 +147,57 @@ pub async fn setup_libraries<D: Send + Sync>(
 
     Ok(())
 }
+
+/// Returns the libraries and natives which apply to this system, without downloading them
+pub fn resolve_libraries(
+    libraries_folder: &Path,
+    version_profile: &VersionProfile,
+    features: &HashSet<String>,
+) -> Result<(Vec<PlannedArtifact>, Vec<PlannedArtifact>)> {
+    let mut libraries = vec![];
+    let mut natives = vec![];
+
+    for library in &version_profile.libraries {
+        if !rule_interpreter::check_condition(&library.rules, features).unwrap_or(false) {
+            continue;
+        }
+
+        let planned = |artifact: LibraryDownloadInfo| {
+            let path = libraries_folder.join(&artifact.path);
+            PlannedArtifact {
+                name: library.name.clone(),
+                present: path.exists(),
+                path,
+                url: artifact.url,
+                sha1: artifact.sha1,
+            }
+        };
+
+        if let Some(library_natives) = &library.natives {
+            if let Some(required_natives) = library_natives.get(OS.get_simple_name()?) {
+                let classifiers = library
+                    .downloads
+                    .as_ref()
+                    .and_then(|x| x.classifiers.as_ref())
+                    .ok_or_else(|| {
+                        LauncherError::InvalidVersionProfile(
+                            "missing classifiers, but natives required.".to_string(),
+                        )
+                    })?;
+
+                if let Some(artifact) = classifiers
+                    .get(required_natives)
+                    .map(LibraryDownloadInfo::from)
+                {
+                    natives.push(planned(artifact));
+                }
+            }
+
+            continue;
+        }
+
+        libraries.push(planned(library.get_library_download()?));
+    }
+
+    Ok((libraries, natives))
+}
@@ -0,0 +1,258 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use path_absolutize::Absolutize;
+use tokio::fs;
+use tracing::warn;
+
+use crate::{
+    minecraft::{
+        progress::ProgressUpdate,
+        version::{LoggingConfiguration, VersionProfile},
+    },
+    utils::{download_file_untracked, Checksum},
+};
+
+use super::{LauncherData, LauncherFrontend};
+
+/// Type of the Log4j 2 configurations, the only one our configuration replaces
+const LOG4J2_XML: &str = "log4j2-xml";
+
+///
+/// Our configuration, the console emits every event as `<log4j:Event>` XML, including the debug events of
+/// the client which the launcher can filter by level. `logs/latest.log` is written in the format of Mojang.
+///
+/// Messages containing a lookup are dropped like in the configurations of Mojang,
+/// since the Log4j versions of older games resolve them.
+///
+const STRUCTURED_CONFIG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Configuration status="WARN"@PACKAGES@>
+    <Filters>
+        <MarkerFilter marker="NETWORK_PACKETS" onMatch="DENY" onMismatch="NEUTRAL"/>
+        <RegexFilter regex="(?s).*\$\{[^}]*\}.*" onMatch="DENY" onMismatch="NEUTRAL"/>
+    </Filters>
+    <Appenders>
+        <Console name="SysOut" target="SYSTEM_OUT">
+            <@LAYOUT@ />
+        </Console>
+        <RollingRandomAccessFile name="File" fileName="logs/latest.log" filePattern="logs/%d{yyyy-MM-dd}-%i.log.gz">
+            <PatternLayout pattern="[%d{HH:mm:ss}] [%t/%level]: %msg%n" />
+            <Policies>
+                <TimeBasedTriggeringPolicy />
+                <OnStartupTriggeringPolicy />
+            </Policies>
+        </RollingRandomAccessFile>
+    </Appenders>
+    <Loggers>
+        <Logger name="net.ccbluex" level="debug" additivity="false">
+            <AppenderRef ref="SysOut"/>
+            <AppenderRef ref="File" level="info"/>
+        </Logger>
+        <Root level="info">
+            <AppenderRef ref="SysOut"/>
+            <AppenderRef ref="File"/>
+        </Root>
+    </Loggers>
+</Configuration>
+"#;
+
+/// Returns the Log4j configuration of the version profile, versions before 1.7 don't use Log4j
+fn client_configuration(version_profile: &VersionProfile) -> Option<&LoggingConfiguration> {
+    version_profile
+        .logging
+        .as_ref()
+        .and_then(|logging| logging.client.as_ref())
+}
+
+fn uses_structured(configuration: &LoggingConfiguration, structured: bool) -> bool {
+    structured && configuration.config_type == LOG4J2_XML
+}
+
+fn structured_id(configuration: &LoggingConfiguration) -> String {
+    format!("liquidlauncher-{}", configuration.file.id)
+}
+
+///
+/// Returns our configuration with the XML layout of the Log4j version of the game, which is told by the
+/// configuration of Mojang. Log4j 2.0 has `XMLLayout` built in, later versions need the `LegacyXMLLayout` of Mojang.
+/// Returns None if the configuration of Mojang uses neither.
+///
+fn structured_config(mojang_config: &str) -> Option<String> {
+    let (packages, layout) = if mojang_config.contains("<LegacyXMLLayout") {
+        (r#" packages="com.mojang.util""#, "LegacyXMLLayout")
+    } else if mojang_config.contains("<XMLLayout") {
+        ("", "XMLLayout")
+    } else {
+        return None;
+    };
+
+    Some(
+        STRUCTURED_CONFIG
+            .replace("@PACKAGES@", packages)
+            .replace("@LAYOUT@", layout),
+    )
+}
+
+///
+/// Resolves the path of the configuration file, the id comes from the version profile and must not leave the folder
+///
+fn config_path(log_configs_folder: &Path, id: &str) -> Result<PathBuf> {
+    if Path::new(id).file_name().and_then(|name| name.to_str()) != Some(id) {
+        bail!("Invalid logging configuration id: {}", id);
+    }
+    Ok(log_configs_folder.join(id))
+}
+
+fn argument(configuration: &LoggingConfiguration, path: &Path) -> Result<String> {
+    Ok(configuration
+        .argument
+        .replace("${path}", &path.absolutize()?.to_string_lossy()))
+}
+
+///
+/// Returns the JVM argument pointing to the configuration without downloading it, e.g. for a dry run
+///
+pub fn logging_argument(
+    assets_folder: &Path,
+    version_profile: &VersionProfile,
+    structured: bool,
+) -> Result<Option<String>> {
+    let Some(configuration) = client_configuration(version_profile) else {
+        return Ok(None);
+    };
+
+    let log_configs_folder = assets_folder.join("log_configs");
+    let id = if uses_structured(configuration, structured) {
+        structured_id(configuration)
+    } else {
+        configuration.file.id.clone()
+    };
+    let path = config_path(&log_configs_folder, &id)?;
+    Ok(Some(argument(configuration, &path)?))
+}
+
+///
+/// Downloads and verifies the Log4j configuration of the version profile into `assets/log_configs`,
+/// and writes our structured configuration next to it if enabled. Returns the JVM argument pointing to the one in use.
+///
+pub async fn setup_logging_config<D: LauncherFrontend>(
+    assets_folder: &Path,
+    version_profile: &VersionProfile,
+    structured: bool,
+    launcher_data: &LauncherData<D>,
+) -> Result<Option<String>> {
+    let Some(configuration) = client_configuration(version_profile) else {
+        return Ok(None);
+    };
+
+    let log_configs_folder = assets_folder.join("log_configs");
+    fs::create_dir_all(&log_configs_folder).await?;
+
+    let file = &configuration.file;
+    let path = config_path(&log_configs_folder, &file.id)?;
+    let checksum = Checksum::sha1(&file.sha1);
+
+    if !path.exists() || checksum.verify(&path).await.is_err() {
+        launcher_data.progress_update(ProgressUpdate::set_label("Downloading logging configuration..."));
+
+        let hash = download_file_untracked(&file.url, &path).await?;
+        if !checksum.matches(&hash) {
+            // Never point the game to a configuration we can't trust
+            let _ = fs::remove_file(&path).await;
+            bail!("SHA1 of logging configuration {} does not match.", file.id);
+        }
+    }
+
+    if uses_structured(configuration, structured) {
+        match structured_config(&fs::read_to_string(&path).await?) {
+            Some(content) => {
+                let structured_path = config_path(&log_configs_folder, &structured_id(configuration))?;
+                if fs::read_to_string(&structured_path).await.ok().as_deref() != Some(content.as_str()) {
+                    fs::write(&structured_path, content).await?;
+                }
+                return Ok(Some(argument(configuration, &structured_path)?));
+            }
+            None => warn!(
+                "Logging configuration {} uses an unknown layout, using it instead of the structured one",
+                file.id
+            ),
+        }
+    }
+
+    Ok(Some(argument(configuration, &path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOJANG_CONFIG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Configuration status="WARN" packages="com.mojang.util">
+    <Appenders>
+        <Console name="SysOut" target="SYSTEM_OUT">
+            <LegacyXMLLayout />
+        </Console>
+        <RollingRandomAccessFile name="File" fileName="logs/latest.log" filePattern="logs/%d{yyyy-MM-dd}-%i.log.gz">
+            <PatternLayout pattern="[%d{HH:mm:ss}] [%t/%level]: %msg{nolookups}%n" />
+        </RollingRandomAccessFile>
+    </Appenders>
+    <Loggers>
+        <Root level="info">
+            <AppenderRef ref="SysOut"/>
+            <AppenderRef ref="File"/>
+        </Root>
+    </Loggers>
+</Configuration>
+"#;
+
+    #[test]
+    fn uses_own_config_with_legacy_xml_layout() {
+        let mojang_config = MOJANG_CONFIG.replace("%msg{nolookups}%n", "%logger %msg{nolookups}%n");
+        let config = structured_config(&mojang_config).unwrap();
+
+        assert!(config.contains(r#"<Configuration status="WARN" packages="com.mojang.util">"#));
+        assert!(config.contains("<LegacyXMLLayout />"));
+        assert!(config.contains(r#"<Logger name="net.ccbluex" level="debug" additivity="false">"#));
+        assert!(config.contains(r#"<RegexFilter regex="(?s).*\$\{[^}]*\}.*""#));
+        // Nothing of the configuration of Mojang is taken over
+        assert!(!config.contains("%logger"));
+        assert!(!config.contains('@'));
+    }
+
+    #[test]
+    fn uses_built_in_xml_layout_of_log4j_2_0() {
+        let mojang_config = MOJANG_CONFIG
+            .replace(r#" packages="com.mojang.util""#, "")
+            .replace("<LegacyXMLLayout />", "<XMLLayout />");
+        let config = structured_config(&mojang_config).unwrap();
+
+        assert!(config.contains(r#"<Configuration status="WARN">"#));
+        assert!(config.contains("<XMLLayout />"));
+        assert!(!config.contains("LegacyXMLLayout"));
+    }
+
+    #[test]
+    fn keeps_configs_with_unknown_layouts() {
+        let config = MOJANG_CONFIG.replace("<LegacyXMLLayout />", "<PatternLayout pattern=\"%msg%n\" />");
+        assert!(structured_config(&config).is_none());
+    }
+}
@@ -19,34 +19,46 @@
 
 use std::collections::HashSet;
 use std::fs;
+use std::future::Future;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
-use std::process::exit;
 
 use anyhow::{bail, Context, Result};
 
+use minecraft_auth::java::JavaAuthManager;
+use minecraft_auth::msa::constants::JAVA_TITLE_ID;
 use path_absolutize::Absolutize;
+use tauri::Url;
 use tokio::{io, spawn};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tracing::*;
+use uuid::Uuid;
 
-use crate::app::client_api::{Client, LaunchManifest};
+use crate::app::client_api::{Client, LaunchManifest, LoaderMod};
 use crate::auth::ClientAccount;
 use crate::error::LauncherError;
+use crate::minecraft::auth::MinecraftAccount;
+use crate::minecraft::cache::BuildCache;
 use crate::minecraft::java::{DistributionSelection, JavaRuntime};
+use crate::minecraft::crash::{self, CrashReport};
+use crate::minecraft::java::output::OutputLine;
 use crate::minecraft::progress::{ProgressReceiver, ProgressUpdate};
+use crate::minecraft::session::{SessionHistory, SessionRecord, Termination};
+use crate::minecraft::session_log::{self, SessionLog};
 use crate::{join_and_mkdir, join_and_mkdir_vec};
 use crate::{
     utils::{downloaded_bytes, OS, OS_VERSION},
-    LAUNCHER_VERSION,
+    HTTP_CLIENT, LAUNCHER_DIRECTORY, LAUNCHER_VERSION,
 };
-use crate::app::options::MinecraftInstallationOptions;
+use crate::app::options::{MinecraftInstallationOptions, Options};
 use self::assets::setup_assets;
 use self::client_jar::setup_client_jar;
 use self::jre::load_jre;
+pub(crate) use self::jre::check_custom_java;
 use self::libraries::setup_libraries;
+use self::logging::setup_logging_config;
 
 use super::version::VersionProfile;
 
@@ -54,38 +66,53 @@ mod assets;
 mod client_jar;
 mod jre;
 mod libraries;
+pub mod gc;
+pub mod logging;
+pub mod plan;
+pub mod repair;
+
+/// Front-end which the launch pipeline reports to, e.g. the launcher window or the command line.
+pub trait LauncherFrontend: Send + Sync {
+    /// Receives a line of output the game wrote to stdout or stderr
+    fn on_output(&self, line: &OutputLine) -> Result<()>;
+    fn on_progress(&self, progress_update: ProgressUpdate) -> Result<()>;
+    fn on_log(&self, msg: &str) -> Result<()>;
+    /// Called once the game process is started, with its PID if the platform reports one
+    fn on_process_started(&self, pid: Option<u32>);
+    /// Called after the game crashed and crash reports explaining it were found
+    fn on_crash(&self, report: &CrashReport);
+    /// Called when the game is running and the launcher should get out of the way
+    fn hide_window(&self);
+    /// Shows the skip-ad download page to the user and resolves the PID of the file behind it.
+    /// This requires user interaction, front-ends which are not able to do so should fail.
+    fn show_download_page(&self, url: Url) -> impl Future<Output = Result<String>>;
+}
 
-pub struct LauncherData<D: Send + Sync> {
-    pub(crate) on_stdout: fn(&D, &[u8]) -> Result<()>,
-    pub(crate) on_stderr: fn(&D, &[u8]) -> Result<()>,
-    pub(crate) on_progress: fn(&D, ProgressUpdate) -> Result<()>,
-    pub(crate) on_log: fn(&D, &str) -> Result<()>,
-    pub(crate) hide_window: fn(&D),
+pub struct LauncherData<D: LauncherFrontend> {
     pub(crate) data: Box<D>,
     pub(crate) terminator: tokio::sync::oneshot::Receiver<()>,
 }
 
-impl<D: Send + Sync> LauncherData<D> {
+impl<D: LauncherFrontend> LauncherData<D> {
     fn hide_window(&self) {
-        (self.hide_window)(&self.data);
+        self.data.hide_window();
     }
 }
 
-impl<D: Send + Sync> ProgressReceiver for LauncherData<D> {
+impl<D: LauncherFrontend> ProgressReceiver for LauncherData<D> {
     fn progress_update(&self, progress_update: ProgressUpdate) {
-        let _ = (self.on_progress)(&self.data, progress_update);
+        let _ = self.data.on_progress(progress_update);
     }
     fn log(&self, msg: &str) {
-        let _ = (self.on_log)(&self.data, msg);
+        let _ = self.data.on_log(msg);
     }
 }
 
-impl<D: Send + Sync + Clone + 'static> LauncherData<D> {
+impl<D: LauncherFrontend + Clone + 'static> LauncherData<D> {
     pub(crate) fn start_speed_meter(&self) -> DownloadSpeedMeter<D> {
         const SPEED_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
         
         let data = (*self.data).clone();
-        let on_progress = self.on_progress;
 
         let task = spawn({
             let data = data.clone();
@@ -113,42 +140,39 @@ impl<D: Send + Sync + Clone + 'static> LauncherData<D> {
                     
                     if speed != reported {
                         reported = speed;
-                        let _ = on_progress(&data, ProgressUpdate::SetDownloadSpeed(speed));
+                        let _ = data.on_progress(ProgressUpdate::SetDownloadSpeed(speed));
                     }
                 }
             }
         });
 
-        DownloadSpeedMeter {
-            task,
-            data,
-            on_progress,
-        }
+        DownloadSpeedMeter { task, data }
     }
 }
 
-pub(crate) struct DownloadSpeedMeter<D: Send + Sync> {
+pub(crate) struct DownloadSpeedMeter<D: LauncherFrontend> {
     task: JoinHandle<()>,
     data: D,
-    on_progress: fn(&D, ProgressUpdate) -> Result<()>,
 }
 
-impl<D: Send + Sync> Drop for DownloadSpeedMeter<D> {
+impl<D: LauncherFrontend> Drop for DownloadSpeedMeter<D> {
     fn drop(&mut self) {
         self.task.abort();
-        let _ = (self.on_progress)(&self.data, ProgressUpdate::SetDownloadSpeed(0));
+        let _ = self.data.on_progress(ProgressUpdate::SetDownloadSpeed(0));
     }
 }
 
 ///
 /// Launches the game
 ///
-pub async fn launch<D: Send + Sync>(
+pub async fn launch<D: LauncherFrontend>(
     data: &Path,
     manifest: LaunchManifest,
     version_profile: VersionProfile,
     launching_parameter: StartParameter,
     launcher_data: LauncherData<D>,
+    mut session: SessionRecord,
+    mods: Vec<LoaderMod>,
 ) -> Result<()> {
     let features: HashSet<String> = HashSet::new();
     let mut class_path = String::new();
@@ -166,7 +190,8 @@ pub async fn launch<D: Send + Sync>(
     let natives_folder = join_and_mkdir!(client_folder, "natives");
     let libraries_folder = join_and_mkdir!(data, "libraries");
     let assets_folder = join_and_mkdir!(data, "assets");
-    let game_dir = join_and_mkdir_vec!(data, vec!["gameDir", &*manifest.build.branch]);
+    let game_dir = launching_parameter.game_directory(&manifest.build.branch);
+    fs::create_dir_all(&game_dir)?;
 
     // Setup vanilla integration symlinks
     setup_installation_link(&game_dir, &launching_parameter.vanilla_integration, &launcher_data)?;
@@ -219,16 +244,111 @@ pub async fn launch<D: Send + Sync>(
     .await
     .context("Failed to setup assets")?;
 
+    // Logging, the game also starts without a Log4j configuration
+    let logging_argument = match setup_logging_config(
+        &assets_folder,
+        &version_profile,
+        launching_parameter.structured_logging,
+        &launcher_data,
+    )
+    .await
+    {
+        Ok(argument) => argument,
+        Err(e) => {
+            warn!("Failed to setup logging configuration: {:?}", e);
+            launcher_data.log(&format!("Failed to setup logging configuration: {}", e));
+            None
+        }
+    };
+
     // Game
-    let java_runtime = JavaRuntime::new(java_bin);
+    let java_runtime = JavaRuntime::new(java_bin.clone());
+
+    let mapped = build_arguments(
+        &version_profile,
+        &launching_parameter,
+        &features,
+        &LaunchDirectories {
+            game: &game_dir,
+            assets: &assets_folder,
+            natives: &natives_folder,
+        },
+        &class_path,
+        &asset_index_location.id,
+        logging_argument.as_deref(),
+    )?;
+
+    launcher_data.progress_update(ProgressUpdate::set_label("Launching..."));
+    launcher_data.progress_update(ProgressUpdate::set_to_max());
+
+    if let Err(e) = session_log::clean_session_logs(data) {
+        warn!("Failed to clear session logs: {:?}", e);
+    }
+    let session_log = SessionLog::create(data, launching_parameter.instance_name.as_deref(), &manifest.build)
+        .map_err(|e| warn!("Failed to create session log: {:?}", e))
+        .ok();
+
+    let launched_at = SystemTime::now();
+    let mut running_task = java_runtime.execute(mapped, &game_dir).await?;
+    launcher_data.data.on_process_started(running_task.id());
+    session.start(&java_bin);
+
+    launcher_data.progress_update(ProgressUpdate::set_label("Running..."));
+
+    if !launching_parameter.keep_launcher_open {
+        // Hide launcher window
+        launcher_data.hide_window();
+    }
+
+    let session_exit = java_runtime
+        .handle_io(&mut running_task, launcher_data.terminator, &*launcher_data.data, session_log)
+        .await?;
+
+    session.finish(&session_exit);
+    if let Err(e) = SessionHistory::new(data).append(&session).await {
+        warn!("Failed to record session: {:?}", e);
+    }
+
+    // Killed processes are not reported as an error, neither is terminating the game from the launcher
+    if session_exit.termination() == Termination::Crash {
+        if let Some(report) = crash::analyze(&game_dir, launched_at, &mods, &session_exit.stderr_tail) {
+            launcher_data.log(&format!("Crash analysis: {}", report.explanation));
+            launcher_data.data.on_crash(&report);
+        }
+
+        // 7900 = the process was ended by a signal and has no exit code
+        bail!("Process exited with non-zero exit code: {}.", session_exit.code.unwrap_or(7900));
+    }
+
+    Ok(())
+}
+
+/// Directories which are passed to the game through its arguments
+pub(crate) struct LaunchDirectories<'a> {
+    pub game: &'a Path,
+    pub assets: &'a Path,
+    pub natives: &'a Path,
+}
 
+///
+/// Assembles the JVM and game arguments and fills in their templates
+///
+pub(crate) fn build_arguments(
+    version_profile: &VersionProfile,
+    launching_parameter: &StartParameter,
+    features: &HashSet<String>,
+    directories: &LaunchDirectories,
+    class_path: &str,
+    asset_index_id: &str,
+    logging_argument: Option<&str>,
+) -> Result<Vec<String>> {
     let mut command_arguments = Vec::new();
 
     // JVM Args
     version_profile.arguments.add_jvm_args_to_vec(
         &mut command_arguments,
-        &launching_parameter,
-        &features,
+        launching_parameter,
+        features,
     )?;
 
     // Launcher Args (-D<name>=<value>)
@@ -242,6 +362,10 @@ pub async fn launch<D: Send + Sync>(
         command_arguments.push(format!("-Dnet.ccbluex.liquidbounce.account.expires_at={}", client_account.get_expires_at()));
     }
     
+    if let Some(logging_argument) = logging_argument {
+        command_arguments.push(logging_argument.to_string());
+    }
+
     // Custom Arguments
     command_arguments.extend(launching_parameter.jvm_args.iter().cloned());
 
@@ -259,7 +383,7 @@ pub async fn launch<D: Send + Sync>(
     // Game args
     version_profile
         .arguments
-        .add_game_args_to_vec(&mut command_arguments, &features)?;
+        .add_game_args_to_vec(&mut command_arguments, features)?;
 
     let mut mapped: Vec<String> = Vec::with_capacity(command_arguments.len());
 
@@ -269,22 +393,22 @@ pub async fn launch<D: Send + Sync>(
                 "auth_player_name" => output.push_str(&launching_parameter.auth_player_name),
                 "version_name" => output.push_str(&version_profile.id),
                 "game_directory" => {
-                    output.push_str(game_dir.absolutize().unwrap().to_str().unwrap())
+                    output.push_str(directories.game.absolutize().unwrap().to_str().unwrap())
                 }
                 "assets_root" => {
-                    output.push_str(assets_folder.absolutize().unwrap().to_str().unwrap())
+                    output.push_str(directories.assets.absolutize().unwrap().to_str().unwrap())
                 }
-                "assets_index_name" => output.push_str(&asset_index_location.id),
+                "assets_index_name" => output.push_str(asset_index_id),
                 "auth_uuid" => output.push_str(&launching_parameter.auth_uuid),
                 "auth_access_token" => output.push_str(&launching_parameter.auth_access_token),
                 "user_type" => output.push_str(&launching_parameter.user_type),
                 "version_type" => output.push_str(&version_profile.version_type),
                 "natives_directory" => {
-                    output.push_str(natives_folder.absolutize().unwrap().to_str().unwrap())
+                    output.push_str(directories.natives.absolutize().unwrap().to_str().unwrap())
                 }
                 "launcher_name" => output.push_str("LiquidLauncher"),
                 "launcher_version" => output.push_str(LAUNCHER_VERSION),
-                "classpath" => output.push_str(&class_path),
+                "classpath" => output.push_str(class_path),
                 "user_properties" => output.push_str("{}"),
                 "clientid" => output.push_str(&launching_parameter.clientid),
                 "auth_xuid" => output.push_str(&launching_parameter.auth_xuid),
@@ -295,37 +419,7 @@ pub async fn launch<D: Send + Sync>(
         })?);
     }
 
-    launcher_data.progress_update(ProgressUpdate::set_label("Launching..."));
-    launcher_data.progress_update(ProgressUpdate::set_to_max());
-
-    let mut running_task = java_runtime.execute(mapped, &game_dir).await?;
-
-    launcher_data.progress_update(ProgressUpdate::set_label("Running..."));
-
-    if !launching_parameter.keep_launcher_open {
-        // Hide launcher window
-        launcher_data.hide_window();
-    }
-
-    let terminator = launcher_data.terminator;
-    let data = launcher_data.data;
-
-    java_runtime
-        .handle_io(
-            &mut running_task,
-            launcher_data.on_stdout,
-            launcher_data.on_stderr,
-            terminator,
-            &data,
-        )
-        .await?;
-
-    if !launching_parameter.keep_launcher_open {
-        // Hide launcher window
-        exit(0);
-    }
-
-    Ok(())
+    Ok(mapped)
 }
 
 pub struct StartParameter {
@@ -345,6 +439,99 @@ pub struct StartParameter {
     pub client_account: Option<ClientAccount>,
     pub skip_advertisement: bool,
     pub vanilla_integration: MinecraftInstallationOptions,
+    pub offline_mode: bool,
+    pub instance_directory: Option<PathBuf>,
+    /// Name of the launched instance, used to name the session log
+    pub instance_name: Option<String>,
+    pub structured_logging: bool,
+}
+
+impl StartParameter {
+    /// Builds the start parameters from the stored launcher options,
+    /// refreshing the Minecraft session of the selected account.
+    pub(crate) async fn from_options(client: Client, options: Options) -> Result<Self> {
+        let mut offline_mode = options.launcher_options.offline_mode || client.is_offline();
+        let minecraft_account = options
+            .start_options
+            .minecraft_account
+            .context("no account selected")?;
+        let (account_name, uuid, token, user_type) = match minecraft_account {
+            MinecraftAccount::MsaAccount { state, name, id } => {
+                let manager = JavaAuthManager::from_json(HTTP_CLIENT.clone(), &state)
+                    .context("unable to load account")?;
+                let token = match manager.minecraft_token().await {
+                    Ok(token) => token.access_token,
+                    // Singleplayer works without a valid session, so cached builds are launched offline
+                    Err(e) => {
+                        if !offline_mode
+                            && !BuildCache::has_cached_builds(&options.start_options.data_directory()).await
+                        {
+                            return Err(e).context("unable to refresh account");
+                        }
+
+                        warn!("Unable to refresh account, launching offline: {}", e);
+                        offline_mode = true;
+                        "-".to_string()
+                    }
+                };
+                (name, id.to_string(), token, "msa".to_string())
+            }
+            MinecraftAccount::OfflineAccount { name, id, .. } => {
+                (name, id.to_string(), "-".to_string(), "legacy".to_string())
+            }
+        };
+
+        let client_account = options.premium_options.account;
+        let skip_advertisement = options.premium_options.skip_advertisement
+            && client_account
+            .as_ref()
+            .is_some_and(|x| x.get_user_information().is_some_and(|u| u.premium));
+
+        // Random XUID
+        let xuid = Uuid::new_v4().to_string();
+
+        Ok(StartParameter {
+            java_distribution: options.start_options.java_distribution,
+            jvm_args: options.start_options.jvm_args.unwrap_or_else(|| vec![]),
+            memory: options.start_options.memory,
+            custom_data_path: if !options.start_options.custom_data_path.is_empty() {
+                Some(options.start_options.custom_data_path)
+            } else {
+                None
+            },
+            auth_player_name: account_name,
+            auth_uuid: uuid,
+            auth_access_token: token,
+            auth_xuid: xuid,
+            clientid: JAVA_TITLE_ID.to_string(),
+            user_type,
+            keep_launcher_open: options.launcher_options.keep_launcher_open,
+            concurrent_downloads: options.launcher_options.concurrent_downloads,
+            client,
+            client_account,
+            skip_advertisement,
+            vanilla_integration: options.start_options.installation,
+            offline_mode,
+            instance_directory: None,
+            instance_name: None,
+            structured_logging: options.launcher_options.structured_logging,
+        })
+    }
+
+    /// Returns the custom data path if set, otherwise the default data directory of the launcher
+    pub fn data_directory(&self) -> PathBuf {
+        self.custom_data_path
+            .clone()
+            .map(|x| x.into())
+            .unwrap_or_else(|| LAUNCHER_DIRECTORY.data_dir().to_path_buf())
+    }
+
+    /// Returns the directory of the launched instance, otherwise the shared game directory of the branch
+    pub fn game_directory(&self, branch: &str) -> PathBuf {
+        self.instance_directory
+            .clone()
+            .unwrap_or_else(|| self.data_directory().join("gameDir").join(branch))
+    }
 }
 
 fn process_templates<F: Fn(&mut String, &str) -> Result<()>>(
@@ -395,7 +582,7 @@ fn process_templates<F: Fn(&mut String, &str) -> Result<()>>(
     Ok(output)
 }
 
-fn setup_installation_link<D: Send + Sync>(
+fn setup_installation_link<D: LauncherFrontend>(
     game_dir: &Path,
     minecraft_installation: &MinecraftInstallationOptions,
     launcher_data: &LauncherData<D>,
@@ -474,7 +661,23 @@ pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<
     for entry in fs::read_dir(src)? {
         let entry = entry?;
         let ty = entry.file_type()?;
-        if ty.is_dir() {
+        // Links like the vanilla saves are kept as links, instead of copying what they point to
+        if ty.is_symlink() {
+            let link = fs::read_link(entry.path())?;
+            let target = dst.as_ref().join(entry.file_name());
+
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&link, &target)?;
+
+            #[cfg(windows)]
+            {
+                if entry.path().is_dir() {
+                    std::os::windows::fs::symlink_dir(&link, &target)?;
+                } else {
+                    std::os::windows::fs::symlink_file(&link, &target)?;
+                }
+            }
+        } else if ty.is_dir() {
             copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
         } else {
             fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
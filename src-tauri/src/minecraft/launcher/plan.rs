@@ -0,0 +1,216 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use path_absolutize::Absolutize;
+use serde::Serialize;
+
+use crate::app::client_api::{Build, LaunchManifest, ModSource};
+use crate::error::LauncherError;
+use crate::minecraft::version::VersionProfile;
+use crate::utils::OS;
+
+use super::client_jar::resolve_client_jar;
+use super::jre::resolve_jre;
+use super::libraries::resolve_libraries;
+use super::logging::logging_argument;
+use super::{build_arguments, LaunchDirectories, StartParameter};
+
+/// Replaces tokens and other values which must not end up in a shared plan
+const REDACTED: &str = "<redacted>";
+
+/// Replaces the data directory in the arguments, like the templates of the version profiles
+const DATA_DIRECTORY: &str = "${data_directory}";
+
+///
+/// Everything the launcher would do to start a build, resolved without downloading anything or starting Java.
+///
+/// Paths inside the data directory are relative to it, so plans of different machines and runs can be compared.
+///
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchPlan {
+    pub build: Build,
+    pub version_id: String,
+    pub java: PlannedJava,
+    pub game_directory: PathBuf,
+    pub client_jar: PlannedArtifact,
+    pub libraries: Vec<PlannedArtifact>,
+    pub natives: Vec<PlannedArtifact>,
+    pub mods: Vec<PlannedMod>,
+    pub class_path: Vec<PathBuf>,
+    pub main_class: String,
+    pub arguments: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedJava {
+    pub distribution: String,
+    pub version: u32,
+    pub binary: Option<PathBuf>,
+    pub download_required: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedArtifact {
+    pub name: String,
+    pub path: PathBuf,
+    pub url: String,
+    pub sha1: Option<String>,
+    pub present: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedMod {
+    pub name: String,
+    pub source: ModSource,
+    pub path: PathBuf,
+    pub present: bool,
+}
+
+///
+/// Resolves the JRE, libraries, class path and arguments the same way [super::launch] does
+///
+pub async fn plan(
+    data: &Path,
+    manifest: LaunchManifest,
+    version_profile: VersionProfile,
+    launching_parameter: &StartParameter,
+    mods: Vec<PlannedMod>,
+) -> Result<LaunchPlan> {
+    let features: HashSet<String> = HashSet::new();
+
+    let runtimes_folder = data.join("runtimes");
+    let client_folder = data.join("versions").join(&version_profile.id);
+    let natives_folder = client_folder.join("natives");
+    let libraries_folder = data.join("libraries");
+    let assets_folder = data.join("assets");
+    let game_dir = launching_parameter.game_directory(&manifest.build.branch);
+
+    let java = resolve_jre(&runtimes_folder, &manifest, launching_parameter).await?;
+    let client_jar = resolve_client_jar(&client_folder, &version_profile)?;
+    let (libraries, natives) = resolve_libraries(&libraries_folder, &version_profile, &features)?;
+
+    let class_path = std::iter::once(&client_jar)
+        .chain(libraries.iter())
+        .map(|artifact| Ok(artifact.path.absolutize()?.to_path_buf()))
+        .collect::<Result<Vec<_>>>()?;
+    let separator = OS.get_path_separator()?;
+    let joined_class_path = class_path
+        .iter()
+        .map(|path| format!("{}{}", path.display(), separator))
+        .collect::<String>();
+
+    let asset_index_location = version_profile
+        .asset_index_location
+        .as_ref()
+        .ok_or_else(|| {
+            LauncherError::InvalidVersionProfile("Asset index unspecified".to_string())
+        })?;
+
+    let logging_argument = logging_argument(&assets_folder, &version_profile, launching_parameter.structured_logging)?;
+
+    let arguments = build_arguments(
+        &version_profile,
+        launching_parameter,
+        &features,
+        &LaunchDirectories {
+            game: &game_dir,
+            assets: &assets_folder,
+            natives: &natives_folder,
+        },
+        &joined_class_path,
+        &asset_index_location.id,
+        logging_argument.as_deref(),
+    )?;
+
+    let data = data.absolutize()?.to_path_buf();
+    let data_string = data.to_string_lossy().to_string();
+    let relative_artifact = |artifact: PlannedArtifact| PlannedArtifact {
+        path: relative(&data, artifact.path),
+        ..artifact
+    };
+
+    Ok(LaunchPlan {
+        arguments: redact(arguments, launching_parameter)
+            .into_iter()
+            .map(|argument| argument.replace(&data_string, DATA_DIRECTORY))
+            .collect(),
+        main_class: version_profile.main_class.clone().unwrap_or_default(),
+        version_id: version_profile.id,
+        build: manifest.build,
+        java: PlannedJava {
+            binary: java.binary.map(|binary| relative(&data, binary)),
+            ..java
+        },
+        game_directory: relative(&data, game_dir),
+        client_jar: relative_artifact(client_jar),
+        libraries: libraries.into_iter().map(relative_artifact).collect(),
+        natives: natives.into_iter().map(relative_artifact).collect(),
+        mods: mods
+            .into_iter()
+            .map(|planned_mod| PlannedMod {
+                path: relative(&data, planned_mod.path),
+                ..planned_mod
+            })
+            .collect(),
+        class_path: class_path.into_iter().map(|path| relative(&data, path)).collect(),
+    })
+}
+
+/// Returns the path relative to the data directory if it is inside of it, e.g. a system Java stays absolute
+fn relative(data: &Path, path: PathBuf) -> PathBuf {
+    let relative = path
+        .absolutize()
+        .ok()
+        .and_then(|absolute| absolute.strip_prefix(data).ok().map(Path::to_path_buf));
+    relative.unwrap_or(path)
+}
+
+/// Removes the session tokens from the arguments, as well as the XUID which is random for every launch
+fn redact(arguments: Vec<String>, launching_parameter: &StartParameter) -> Vec<String> {
+    let mut secrets = vec![
+        launching_parameter.auth_access_token.clone(),
+        launching_parameter.auth_xuid.clone(),
+        launching_parameter.client.session_token().to_string(),
+    ];
+
+    if let Some(client_account) = &launching_parameter.client_account {
+        secrets.push(client_account.get_access_token().secret().clone());
+        secrets.push(client_account.get_refresh_token().secret().clone());
+    }
+
+    // Offline accounts use "-" as their access token
+    secrets.retain(|secret| secret.len() > 1);
+
+    arguments
+        .into_iter()
+        .map(|argument| {
+            secrets
+                .iter()
+                .fold(argument, |argument, secret| argument.replace(secret, REDACTED))
+        })
+        .collect()
+}
@@ -0,0 +1,454 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use futures::{stream, StreamExt};
+use serde::Serialize;
+use tokio::fs;
+use tracing::warn;
+
+use crate::app::client_api::{LaunchManifest, LoaderMod, ModSource};
+use crate::minecraft::java::{
+    candidate_distributions, find_installed_java, find_java_binary, java_command, jre_download, jre_install,
+    resolve_download, DistributionSelection,
+};
+use crate::minecraft::prelauncher::{mod_path, retrieve_and_copy_mods};
+use crate::minecraft::progress::{ProgressReceiver, ProgressUpdate};
+use crate::minecraft::version::{AssetIndex, VersionProfile};
+use crate::utils::{download_file, hash_file_async, Checksum, HashAlgorithm};
+
+use super::client_jar::resolve_client_jar;
+use super::libraries::resolve_libraries;
+use super::plan::PlannedArtifact;
+use super::{LauncherData, LauncherFrontend, StartParameter};
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ArtifactKind {
+    ClientJar,
+    Library,
+    Native,
+    AssetIndex,
+    Asset,
+    Runtime,
+    Mod,
+}
+
+/// What is wrong with a file of the installation
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Problem {
+    Missing,
+    SizeMismatch {
+        expected: u64,
+        actual: u64,
+    },
+    HashMismatch {
+        algorithm: HashAlgorithm,
+        expected: String,
+        actual: String,
+    },
+    Unusable {
+        reason: String,
+    },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Missing => write!(f, "missing"),
+            Problem::SizeMismatch { expected, actual } => {
+                write!(f, "{} bytes instead of {} bytes", actual, expected)
+            }
+            Problem::HashMismatch { algorithm, expected, actual } => {
+                write!(f, "corrupted, {} is {} instead of {}", algorithm, actual, expected)
+            }
+            Problem::Unusable { reason } => write!(f, "unusable: {}", reason),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairIssue {
+    pub kind: ArtifactKind,
+    pub name: String,
+    pub path: PathBuf,
+    pub problem: Problem,
+    pub repaired: bool,
+    pub error: Option<String>,
+}
+
+impl RepairIssue {
+    fn new(kind: ArtifactKind, name: String, path: PathBuf, problem: Problem) -> Self {
+        Self { kind, name, path, problem, repaired: false, error: None }
+    }
+
+    fn resolve(&mut self, result: Result<()>) {
+        match result {
+            Ok(()) => self.repaired = true,
+            Err(e) => self.error = Some(format!("{:#}", e)),
+        }
+    }
+}
+
+///
+/// Result of [repair], lists every file which was missing or corrupted
+///
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    pub checked: usize,
+    pub issues: Vec<RepairIssue>,
+}
+
+/// A downloadable file which is expected to be part of the installation
+struct ExpectedFile {
+    kind: ArtifactKind,
+    name: String,
+    path: PathBuf,
+    url: String,
+    checksum: Option<Checksum>,
+    size: Option<u64>,
+}
+
+impl ExpectedFile {
+    fn from_artifact(kind: ArtifactKind, artifact: PlannedArtifact) -> Self {
+        // Libraries without a SHA1 in the version profile have it stored next to them
+        let sha1 = artifact
+            .sha1
+            .or_else(|| std::fs::read_to_string(artifact.path.with_extension("sha1")).ok());
+
+        Self {
+            kind,
+            name: artifact.name,
+            checksum: sha1.map(Checksum::sha1),
+            path: artifact.path,
+            url: artifact.url,
+            size: None,
+        }
+    }
+}
+
+///
+/// Checks the client, libraries, assets, JRE and mods of the build against what they are expected to be,
+/// and downloads the missing or corrupted files again if [fix] is set
+///
+pub async fn repair<D: LauncherFrontend>(
+    data: &Path,
+    manifest: &LaunchManifest,
+    version_profile: &VersionProfile,
+    mods: &[LoaderMod],
+    launching_parameter: &StartParameter,
+    launcher_data: &LauncherData<D>,
+    fix: bool,
+) -> Result<RepairReport> {
+    let features: HashSet<String> = HashSet::new();
+    let mut report = RepairReport::default();
+
+    let client_folder = data.join("versions").join(&version_profile.id);
+    let assets_folder = data.join("assets");
+
+    launcher_data.progress_update(ProgressUpdate::set_label("Checking client and libraries..."));
+    let client_jar = resolve_client_jar(&client_folder, version_profile)?;
+    let (libraries, natives) =
+        resolve_libraries(&data.join("libraries"), version_profile, &features)?;
+
+    let mut files = vec![ExpectedFile::from_artifact(ArtifactKind::ClientJar, client_jar)];
+    files.extend(
+        libraries
+            .into_iter()
+            .map(|artifact| ExpectedFile::from_artifact(ArtifactKind::Library, artifact)),
+    );
+    files.extend(
+        natives
+            .into_iter()
+            .map(|artifact| ExpectedFile::from_artifact(ArtifactKind::Native, artifact)),
+    );
+    check_files(files, launching_parameter, launcher_data, fix, &mut report).await;
+
+    if let Some(location) = &version_profile.asset_index_location {
+        launcher_data.progress_update(ProgressUpdate::set_label("Checking assets..."));
+
+        let index_path = assets_folder
+            .join("indexes")
+            .join(format!("{}.json", location.id));
+        let index_file = ExpectedFile {
+            kind: ArtifactKind::AssetIndex,
+            name: location.id.clone(),
+            path: index_path.clone(),
+            url: location.url.clone(),
+            checksum: Some(Checksum::sha1(&location.sha1)),
+            size: Some(location.size as u64),
+        };
+        check_files(vec![index_file], launching_parameter, launcher_data, fix, &mut report).await;
+
+        // The asset objects can only be listed once the index is intact
+        let asset_index = fs::read(&index_path)
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_slice::<AssetIndex>(&content).ok());
+
+        match asset_index {
+            Some(asset_index) => {
+                let objects_folder = assets_folder.join("objects");
+                let files = asset_index
+                    .objects
+                    .into_iter()
+                    .map(|(name, object)| ExpectedFile {
+                        kind: ArtifactKind::Asset,
+                        name,
+                        path: objects_folder.join(&object.hash[0..2]).join(&object.hash),
+                        url: format!(
+                            "https://resources.download.minecraft.net/{}/{}",
+                            &object.hash[0..2],
+                            &object.hash
+                        ),
+                        size: Some(object.size as u64),
+                        checksum: Some(Checksum::sha1(object.hash)),
+                    })
+                    .collect();
+                check_files(files, launching_parameter, launcher_data, fix, &mut report).await;
+            }
+            None => warn!("Asset index {} is not available, skipping asset objects", location.id),
+        }
+    }
+
+    check_runtime(data, manifest, launching_parameter, launcher_data, fix, &mut report).await;
+    check_mods(data, manifest, mods, launching_parameter, launcher_data, fix, &mut report).await?;
+
+    launcher_data.progress_update(ProgressUpdate::set_label(format!(
+        "Checked {} files, {} issues found",
+        report.checked,
+        report.issues.len()
+    )));
+    Ok(report)
+}
+
+async fn check_files<D: LauncherFrontend>(
+    files: Vec<ExpectedFile>,
+    launching_parameter: &StartParameter,
+    launcher_data: &LauncherData<D>,
+    fix: bool,
+    report: &mut RepairReport,
+) {
+    report.checked += files.len();
+
+    let issues: Vec<Option<RepairIssue>> = stream::iter(files.into_iter().map(|file| async move {
+        let problem = match inspect(&file.path, file.checksum.as_ref(), file.size).await {
+            Ok(None) => return None,
+            Ok(Some(problem)) => problem,
+            Err(e) => Problem::Unusable { reason: format!("{:#}", e) },
+        };
+        launcher_data.log(&format!("{} is {}", file.path.display(), problem));
+
+        let mut issue = RepairIssue::new(file.kind, file.name.clone(), file.path.clone(), problem);
+        if fix {
+            issue.resolve(redownload(&file).await);
+        }
+        Some(issue)
+    }))
+    .buffer_unordered(launching_parameter.concurrent_downloads as usize)
+    .collect()
+    .await;
+
+    report.issues.extend(issues.into_iter().flatten());
+}
+
+/// Returns what is wrong with the file, if anything
+async fn inspect(path: &Path, checksum: Option<&Checksum>, size: Option<u64>) -> Result<Option<Problem>> {
+    let Ok(metadata) = fs::metadata(path).await else {
+        return Ok(Some(Problem::Missing));
+    };
+
+    if let Some(expected) = size {
+        if metadata.len() != expected {
+            return Ok(Some(Problem::SizeMismatch { expected, actual: metadata.len() }));
+        }
+    }
+
+    if let Some(checksum) = checksum {
+        let algorithm = checksum.algorithm;
+        let actual = hash_file_async(path, algorithm).await?;
+
+        if !checksum.matches(&actual) {
+            return Ok(Some(Problem::HashMismatch {
+                algorithm,
+                expected: checksum.value.clone(),
+                actual,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+async fn redownload(file: &ExpectedFile) -> Result<()> {
+    if let Some(parent) = file.path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    download_file(&file.url, &file.path, |_, _| {}).await?;
+
+    if let Some(problem) = inspect(&file.path, file.checksum.as_ref(), file.size).await? {
+        bail!("Downloaded file is {}", problem);
+    }
+    Ok(())
+}
+
+/// Makes sure the JRE launches, a broken runtime is downloaded again as a whole
+async fn check_runtime<D: LauncherFrontend>(
+    data: &Path,
+    manifest: &LaunchManifest,
+    launching_parameter: &StartParameter,
+    launcher_data: &LauncherData<D>,
+    fix: bool,
+    report: &mut RepairReport,
+) {
+    let version = manifest.build.jre_version;
+    let distribution = match &launching_parameter.java_distribution {
+        DistributionSelection::Automatic(_) => &manifest.build.jre_distribution,
+        // Custom runtimes are not managed by the launcher
+        DistributionSelection::Custom(_) => return,
+        DistributionSelection::Manual(distribution) => distribution,
+    };
+
+    launcher_data.progress_update(ProgressUpdate::set_label("Checking JRE..."));
+    report.checked += 1;
+
+    // A fallback distribution is used if the selected one has no build for this system
+    let runtimes_folder = data.join("runtimes");
+    let mut installed = None;
+    for candidate in candidate_distributions(distribution, version) {
+        if let Ok(binary) = find_java_binary(&runtimes_folder, &candidate, &version).await {
+            installed = Some((candidate, binary));
+            break;
+        }
+    }
+
+    let (distribution, problem) = match installed {
+        Some((installed, binary)) => match java_version(&binary).await {
+            Ok(()) => return,
+            Err(e) => (installed, Problem::Unusable { reason: format!("{:#}", e) }),
+        },
+        // The launch uses an installed Java instead, so nothing needs to be downloaded
+        None if matches!(launching_parameter.java_distribution, DistributionSelection::Automatic(_))
+            && find_installed_java(version).await.is_some() => return,
+        None => (distribution.clone(), Problem::Missing),
+    };
+    let name = format!("{}_{}", distribution.get_name(), version);
+    launcher_data.log(&format!("JRE {} is {}", name, problem));
+
+    let missing = matches!(problem, Problem::Missing);
+    let mut issue = RepairIssue::new(
+        ArtifactKind::Runtime,
+        name.clone(),
+        runtimes_folder.join(&name),
+        problem,
+    );
+    if fix {
+        launcher_data.progress_update(ProgressUpdate::set_label("Downloading JRE..."));
+        let downloaded = if missing {
+            match resolve_download(&distribution, version).await {
+                Ok(resolution) => jre_install(&runtimes_folder, &resolution, &version, |_, _| {}).await,
+                Err(e) => Err(e),
+            }
+        } else {
+            jre_download(&runtimes_folder, &distribution, &version, |_, _| {}).await
+        };
+        issue.resolve(downloaded.map(|_| ()));
+    }
+    report.issues.push(issue);
+}
+
+/// Runs `java -version`, which fails if files of the runtime are missing or corrupted
+async fn java_version(binary: &Path) -> Result<()> {
+    let output = java_command(binary).arg("-version").output().await?;
+
+    if !output.status.success() {
+        bail!(
+            "java -version failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Checks the cached mods, local mods belong to the user and are left alone
+async fn check_mods<D: LauncherFrontend>(
+    data: &Path,
+    manifest: &LaunchManifest,
+    mods: &[LoaderMod],
+    launching_parameter: &StartParameter,
+    launcher_data: &LauncherData<D>,
+    fix: bool,
+    report: &mut RepairReport,
+) -> Result<()> {
+    launcher_data.progress_update(ProgressUpdate::set_label("Checking mods..."));
+
+    let game_directory = launching_parameter.game_directory(&manifest.build.branch);
+    let retriever_account = if launching_parameter.skip_advertisement {
+        &launching_parameter.client_account
+    } else {
+        &None
+    };
+
+    for current_mod in mods {
+        if (!current_mod.required && !current_mod.enabled)
+            || matches!(current_mod.source, ModSource::Local { .. })
+        {
+            continue;
+        }
+        report.checked += 1;
+
+        let path = mod_path(data, manifest, current_mod)?;
+        let problem = match inspect(&path, current_mod.checksum().as_ref(), None).await {
+            Ok(None) => continue,
+            Ok(Some(problem)) => problem,
+            Err(e) => Problem::Unusable { reason: format!("{:#}", e) },
+        };
+        launcher_data.log(&format!("Mod {} is {}", current_mod.name, problem));
+
+        let mut issue =
+            RepairIssue::new(ArtifactKind::Mod, current_mod.name.clone(), path.clone(), problem);
+        if fix {
+            let _ = fs::remove_file(&path).await;
+            issue.resolve(
+                retrieve_and_copy_mods(
+                    data,
+                    &game_directory,
+                    manifest,
+                    &vec![current_mod.clone()],
+                    &launching_parameter.client,
+                    retriever_account,
+                    launcher_data,
+                )
+                .await,
+            );
+        }
+        report.issues.push(issue);
+    }
+
+    Ok(())
+}
@@ -18,9 +18,13 @@
  */
 
 pub mod auth;
+pub mod cache;
+pub mod crash;
 pub mod java;
 pub mod launcher;
 pub mod prelauncher;
 pub mod progress;
+pub mod session;
+pub mod session_log;
 mod rule_interpreter;
 pub mod version;
@@ -17,64 +17,99 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, bail, Context, Result};
-use async_zip::base::read::mem::ZipFileReader;
 use tokio::fs;
 use tracing::*;
 
 use crate::app::client_api::{Client, LaunchManifest, LoaderMod, LoaderSubsystem, ModSource};
-use crate::app::gui::ShareableWindow;
 use crate::app::webview::open_download_page;
 use crate::auth::ClientAccount;
 use crate::error::LauncherError;
+use crate::minecraft::cache::BuildCache;
+use crate::minecraft::java::DistributionSelection;
 use crate::minecraft::launcher;
-use crate::minecraft::launcher::{LauncherData, StartParameter};
+use crate::minecraft::launcher::plan::{plan, LaunchPlan, PlannedMod};
+use crate::minecraft::launcher::repair::RepairReport;
+use crate::minecraft::launcher::{LauncherData, LauncherFrontend, StartParameter};
 use crate::minecraft::progress::{
     get_max, get_progress, ProgressReceiver, ProgressUpdate, ProgressUpdateSteps,
 };
+use crate::minecraft::session::SessionRecord;
 use crate::minecraft::version::{VersionManifest, VersionProfile};
-use crate::utils::{download_file, get_maven_artifact_path};
-use crate::LAUNCHER_DIRECTORY;
+use crate::utils::{download_file, get_maven_artifact_path, zip_extract_entry};
 
 use backon::{ExponentialBuilder, Retryable};
 
+///
+/// Loads the launch manifest of a build, falls back to the cached one if the API is unreachable
+///
+pub(crate) async fn load_launch_manifest(
+    build_id: u32,
+    launching_parameter: &StartParameter,
+) -> Result<LaunchManifest> {
+    let cache = BuildCache::new(&launching_parameter.data_directory(), build_id);
+
+    if launching_parameter.offline_mode {
+        return cache.load_launch_manifest().await.with_context(|| {
+            format!(
+                "Build {} is not available offline, it has to be launched once while online",
+                build_id
+            )
+        });
+    }
+
+    match launching_parameter
+        .client
+        .fetch_launch_manifest_json(build_id)
+        .await
+    {
+        Ok(json) => {
+            let manifest = serde_json::from_value::<LaunchManifest>(json.clone())?;
+            if let Err(e) = cache.store_launch_manifest(&json).await {
+                warn!("Failed to cache launch manifest of build {}: {:?}", build_id, e);
+            }
+            Ok(manifest)
+        }
+        Err(e) => {
+            warn!(
+                "Failed to fetch launch manifest of build {}, falling back to cache: {:?}",
+                build_id, e
+            );
+            cache.load_launch_manifest().await.map_err(|_| e)
+        }
+    }
+}
+
 ///
 /// Prelaunching client
 ///
-pub(crate) async fn launch(
+pub(crate) async fn launch<D: LauncherFrontend + Clone + 'static>(
     launch_manifest: LaunchManifest,
     launching_parameter: StartParameter,
     additional_mods: Vec<LoaderMod>,
-    launcher_data: LauncherData<ShareableWindow>,
+    launcher_data: LauncherData<D>,
 ) -> Result<()> {
     // Starts the speed meter, kills it when the launch is completed.
     let _speed_meter = launcher_data.start_speed_meter();
 
     launcher_data.progress_update(ProgressUpdate::set_max());
     launcher_data.progress_update(ProgressUpdate::SetProgress(0));
-    launcher_data.progress_update(ProgressUpdate::set_label("Loading version manifest..."));
-
-    let mc_version_manifest = VersionManifest::fetch
-        .retry(ExponentialBuilder::default())
-        .notify(|err, dur| {
-            launcher_data.log(&format!(
-                "Failed to load version manifest. Retrying in {:?}. Error: {}",
-                dur, err
-            ));
-        })
-        .await?;
 
     let client = &launching_parameter.client;
     let build = &launch_manifest.build;
-    let subsystem = &launch_manifest.subsystem;
 
-    let data_directory = launching_parameter
-        .custom_data_path
-        .clone()
-        .map(|x| x.into())
-        .unwrap_or_else(|| LAUNCHER_DIRECTORY.data_dir().to_path_buf());
+    let data_directory = launching_parameter.data_directory();
+    let game_directory = launching_parameter.game_directory(&build.branch);
+    let cache = BuildCache::new(&data_directory, build.build_id);
+
+    // The mod list can't be requested offline, use the mods of the last launch instead
+    let additional_mods = if launching_parameter.offline_mode && additional_mods.is_empty() {
+        cache.load_mods().await.unwrap_or_default()
+    } else {
+        additional_mods
+    };
 
     let retriever_account = if launching_parameter.skip_advertisement {
         &launching_parameter.client_account
@@ -82,10 +117,17 @@ pub(crate) async fn launch(
         &None
     };
 
+    // Fail before anything is downloaded if the custom Java can't run this build
+    if let DistributionSelection::Custom(path) = &launching_parameter.java_distribution {
+        launcher_data.progress_update(ProgressUpdate::set_label("Checking custom Java..."));
+        launcher::check_custom_java(Path::new(path), build.jre_version).await?;
+    }
+
     // Copy retrieve and copy mods from manifest
-    clear_mods(&data_directory, &launch_manifest).await?;
+    clear_mods(&game_directory).await?;
     retrieve_and_copy_mods(
         &data_directory,
+        &game_directory,
         &launch_manifest,
         &launch_manifest.mods,
         client,
@@ -95,6 +137,7 @@ pub(crate) async fn launch(
     .await?;
     retrieve_and_copy_mods(
         &data_directory,
+        &game_directory,
         &launch_manifest,
         &additional_mods,
         client,
@@ -103,6 +146,209 @@ pub(crate) async fn launch(
     )
     .await?;
 
+    if let Err(e) = cache.store_mods(&additional_mods).await {
+        warn!("Failed to cache mods of build {}: {:?}", build.build_id, e);
+    }
+
+    let version = resolve_version_profile(&launch_manifest, &launching_parameter, &cache, &launcher_data).await?;
+
+    launcher_data.progress_update(ProgressUpdate::set_label(format!(
+        "Launching {}...",
+        launch_manifest.build.commit_id
+    )));
+    let session_mods = launch_manifest
+        .mods
+        .iter()
+        .chain(&additional_mods)
+        .filter(|current_mod| current_mod.required || current_mod.enabled)
+        .cloned()
+        .collect::<Vec<_>>();
+    let session = SessionRecord::new(
+        &launch_manifest.build,
+        &session_mods,
+        &launching_parameter.jvm_args,
+        launching_parameter.memory,
+    );
+
+    launcher::launch(
+        &data_directory,
+        launch_manifest,
+        version,
+        launching_parameter,
+        launcher_data,
+        session,
+        session_mods,
+    )
+    .await?;
+    Ok(())
+}
+
+///
+/// Resolves everything [launch] would do without downloading anything or starting the game
+///
+pub(crate) async fn dry_run<D: LauncherFrontend>(
+    launch_manifest: LaunchManifest,
+    launching_parameter: &StartParameter,
+    additional_mods: Vec<LoaderMod>,
+    launcher_data: &LauncherData<D>,
+) -> Result<LaunchPlan> {
+    let data_directory = launching_parameter.data_directory();
+    let cache = BuildCache::new(&data_directory, launch_manifest.build.build_id);
+
+    let additional_mods = if launching_parameter.offline_mode && additional_mods.is_empty() {
+        cache.load_mods().await.unwrap_or_default()
+    } else {
+        additional_mods
+    };
+
+    let mods = launch_manifest
+        .mods
+        .iter()
+        .chain(additional_mods.iter())
+        .filter(|current_mod| current_mod.required || current_mod.enabled)
+        .map(|current_mod| {
+            let path = mod_path(&data_directory, &launch_manifest, current_mod)?;
+            Ok(PlannedMod {
+                name: current_mod.name.clone(),
+                source: current_mod.source.clone(),
+                present: path.exists(),
+                path,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let version =
+        resolve_version_profile(&launch_manifest, launching_parameter, &cache, launcher_data).await?;
+
+    plan(&data_directory, launch_manifest, version, launching_parameter, mods).await
+}
+
+///
+/// Checks the installation of the build and downloads missing or corrupted files again if [fix] is set
+///
+pub(crate) async fn repair<D: LauncherFrontend>(
+    launch_manifest: LaunchManifest,
+    launching_parameter: &StartParameter,
+    additional_mods: Vec<LoaderMod>,
+    launcher_data: &LauncherData<D>,
+    fix: bool,
+) -> Result<RepairReport> {
+    let data_directory = launching_parameter.data_directory();
+    let cache = BuildCache::new(&data_directory, launch_manifest.build.build_id);
+
+    let additional_mods = if launching_parameter.offline_mode && additional_mods.is_empty() {
+        cache.load_mods().await.unwrap_or_default()
+    } else {
+        additional_mods
+    };
+    let mods = launch_manifest
+        .mods
+        .iter()
+        .chain(additional_mods.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let version =
+        resolve_version_profile(&launch_manifest, launching_parameter, &cache, launcher_data).await?;
+
+    launcher::repair::repair(
+        &data_directory,
+        &launch_manifest,
+        &version,
+        &mods,
+        launching_parameter,
+        launcher_data,
+        fix,
+    )
+    .await
+}
+
+/// Returns where the mod is stored before it is copied into the mods folder
+pub(crate) fn mod_path(data: &Path, manifest: &LaunchManifest, current_mod: &LoaderMod) -> Result<PathBuf> {
+    Ok(match &current_mod.source {
+        ModSource::Local { file_name } => data
+            .join("custom_mods")
+            .join(format!("{}-{}", manifest.build.branch, manifest.build.mc_version))
+            .join(file_name),
+        source => data.join("mod_cache").join(source.get_path()?),
+    })
+}
+
+///
+/// Loads the version profile of the build, from the cache if offline or unreachable
+///
+async fn resolve_version_profile<D: LauncherFrontend>(
+    launch_manifest: &LaunchManifest,
+    launching_parameter: &StartParameter,
+    cache: &BuildCache,
+    launcher_data: &LauncherData<D>,
+) -> Result<VersionProfile> {
+    if launching_parameter.offline_mode {
+        launcher_data.progress_update(ProgressUpdate::set_label("Loading cached version profile..."));
+        return cache.load_version_profile().await.with_context(|| {
+            format!(
+                "Build {} is not available offline, it has to be launched once while online",
+                launch_manifest.build.build_id
+            )
+        });
+    }
+
+    match load_version_profile(launch_manifest, cache, launcher_data).await {
+        Ok(version) => {
+            if let Err(e) = cache.store_version_profile(&version).await {
+                warn!(
+                    "Failed to cache version profile of build {}: {:?}",
+                    launch_manifest.build.build_id, e
+                );
+            }
+            Ok(version)
+        }
+        Err(e) => {
+            launcher_data.log(&format!(
+                "Failed to load version profile, falling back to cached version profile. Error: {:?}",
+                e
+            ));
+            cache.load_version_profile().await.map_err(|_| e)
+        }
+    }
+}
+
+///
+/// Loads the version profile of the subsystem and merges it with the version profile it inherits from
+///
+async fn load_version_profile<D: LauncherFrontend>(
+    launch_manifest: &LaunchManifest,
+    cache: &BuildCache,
+    launcher_data: &LauncherData<D>,
+) -> Result<VersionProfile> {
+    let build = &launch_manifest.build;
+    let subsystem = &launch_manifest.subsystem;
+
+    launcher_data.progress_update(ProgressUpdate::set_label("Loading version manifest..."));
+
+    let mc_version_manifest = VersionManifest::fetch
+        .retry(ExponentialBuilder::default())
+        .notify(|err, dur| {
+            launcher_data.log(&format!(
+                "Failed to load version manifest. Retrying in {:?}. Error: {}",
+                dur, err
+            ));
+        })
+        .await;
+
+    let mc_version_manifest = match mc_version_manifest {
+        Ok(mc_version_manifest) => {
+            if let Err(e) = cache.store_version_manifest(&mc_version_manifest).await {
+                warn!("Failed to cache version manifest: {:?}", e);
+            }
+            mc_version_manifest
+        }
+        Err(e) => {
+            warn!("Failed to load version manifest, falling back to cache: {:?}", e);
+            cache.load_version_manifest().await.map_err(|_| e)?
+        }
+    };
+
     launcher_data.progress_update(ProgressUpdate::set_label("Loading version profile..."));
     let manifest_url = match subsystem {
         LoaderSubsystem::Fabric { manifest, .. } => manifest
@@ -157,26 +403,11 @@ pub(crate) async fn launch(
         version.merge(parent_version)?;
     }
 
-    launcher_data.progress_update(ProgressUpdate::set_label(format!(
-        "Launching {}...",
-        launch_manifest.build.commit_id
-    )));
-    launcher::launch(
-        &data_directory,
-        launch_manifest,
-        version,
-        launching_parameter,
-        launcher_data,
-    )
-    .await?;
-    Ok(())
+    Ok(version)
 }
 
-pub(crate) async fn clear_mods(data: &Path, manifest: &LaunchManifest) -> Result<()> {
-    let mods_path = data
-        .join("gameDir")
-        .join(&manifest.build.branch)
-        .join("mods");
+pub(crate) async fn clear_mods(game_dir: &Path) -> Result<()> {
+    let mods_path = game_dir.join("mods");
 
     if !mods_path.exists() {
         return Ok(());
@@ -192,23 +423,21 @@ pub(crate) async fn clear_mods(data: &Path, manifest: &LaunchManifest) -> Result
     Ok(())
 }
 
-pub async fn retrieve_and_copy_mods(
+pub async fn retrieve_and_copy_mods<D: LauncherFrontend>(
     data: &Path,
+    game_dir: &Path,
     manifest: &LaunchManifest,
     mods: &Vec<LoaderMod>,
     client: &Client,
     client_account: &Option<ClientAccount>,
-    launcher_data: &LauncherData<ShareableWindow>,
+    launcher_data: &LauncherData<D>,
 ) -> Result<()> {
     let mod_cache_path = data.join("mod_cache");
     let mod_custom_path = data.join("custom_mods").join(format!(
         "{}-{}",
         manifest.build.branch, manifest.build.mc_version
     ));
-    let mods_path = data
-        .join("gameDir")
-        .join(&manifest.build.branch)
-        .join("mods");
+    let mods_path = game_dir.join("mods");
 
     fs::create_dir_all(&mod_cache_path).await.with_context(|| {
         format!(
@@ -263,13 +492,24 @@ pub async fn retrieve_and_copy_mods(
         )));
 
         let current_mod_path = mod_cache_path.join(current_mod.source.get_path()?);
+        let checksum = current_mod.checksum();
+
+        // Drop cached mods which do not match their checksum anymore
+        if let Some(checksum) = &checksum {
+            if current_mod_path.exists() {
+                if let Err(e) = checksum.verify(&current_mod_path).await {
+                    launcher_data.log(&format!("{}, downloading it again", e));
+                    fs::remove_file(&current_mod_path).await?;
+                }
+            }
+        }
 
         // Do we need to download the mod?
         if !current_mod_path.exists() {
             // Make sure that the parent directory exists
             fs::create_dir_all(&current_mod_path.parent().unwrap()).await?;
 
-            let contents = match &current_mod.source {
+            match &current_mod.source {
                 ModSource::SkipAd {
                     artifact_name: _,
                     url,
@@ -312,47 +552,36 @@ pub async fn retrieve_and_copy_mods(
                         current_mod.name
                     )));
                     
-                    let retrieved_bytes = download_file(&url, |a, b| {
+                    let on_progress = |a: u64, b: u64| {
                         launcher_data.progress_update(ProgressUpdate::set_for_step(
                             ProgressUpdateSteps::DownloadLiquidBounceMods,
                             get_progress(mod_idx, a, b),
                             max,
                         ))
-                    })
-                    .await?;
+                    };
 
-                    // Extract bytes
+                    // Extract JAR from downloaded archive
                     if *extract {
-                        let reader = ZipFileReader::new(retrieved_bytes).await?;
-
-                        // Find .JAR file in archive and get index of it
-                        let index_of_file_to_extract = reader
-                            .file()
-                            .entries()
-                            .iter()
-                            .position(|x| {
-                                x.filename()
-                                    .as_str()
-                                    .is_ok_and(|name| name.ends_with(".jar"))
-                            })
-                            .ok_or_else(|| {
-                                LauncherError::InvalidVersionProfile(
-                                    "There is no JAR in the downloaded archive".to_string(),
-                                )
-                            })?;
-                        let entry = &reader.file().entries()[index_of_file_to_extract];
-
-                        // Read file to extract
-                        let mut output = Vec::with_capacity(entry.uncompressed_size() as usize);
-                        reader
-                            .reader_with_entry(index_of_file_to_extract)
-                            .await?
-                            .read_to_end_checked(&mut output)
-                            .await?;
-
-                        output
+                        let archive_path = current_mod_path.with_extension("zip");
+                        download_file(&url, &archive_path, on_progress).await?;
+
+                        let archive = fs::File::open(&archive_path).await?;
+                        let extracted = zip_extract_entry(
+                            archive,
+                            |name| name.ends_with(".jar"),
+                            &current_mod_path,
+                        )
+                        .await;
+                        fs::remove_file(&archive_path).await?;
+
+                        if !extracted? {
+                            return Err(LauncherError::InvalidVersionProfile(
+                                "There is no JAR in the downloaded archive".to_string(),
+                            )
+                            .into());
+                        }
                     } else {
-                        retrieved_bytes
+                        download_file(&url, &current_mod_path, on_progress).await?;
                     }
                 }
                 ModSource::Repository {
@@ -368,8 +597,9 @@ pub async fn retrieve_and_copy_mods(
                             ))
                         })?;
 
-                    let retrieved_bytes = download_file(
+                    download_file(
                         &format!("{}{}", repository_url, get_maven_artifact_path(artifact)?),
+                        &current_mod_path,
                         |a, b| {
                             launcher_data.progress_update(ProgressUpdate::set_for_step(
                                 ProgressUpdateSteps::DownloadLiquidBounceMods,
@@ -378,16 +608,18 @@ pub async fn retrieve_and_copy_mods(
                             ));
                         },
                     )
-                    .await?;
-
-                    retrieved_bytes
+                    .await
+                    .with_context(|| format!("Failed to download mod {}", current_mod.name))?;
                 }
                 _ => bail!("unsupported mod source: {:?}", current_mod.source),
-            };
+            }
 
-            fs::write(&current_mod_path, contents)
-                .await
-                .with_context(|| format!("Failed to write mod {}", current_mod.name))?;
+            if let Some(checksum) = &checksum {
+                if let Err(e) = checksum.verify(&current_mod_path).await {
+                    fs::remove_file(&current_mod_path).await?;
+                    return Err(e.context(format!("Mod {} is corrupted", current_mod.name)));
+                }
+            }
         }
 
         // Copy the mod.
@@ -401,3 +633,135 @@ pub async fn retrieve_and_copy_mods(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use tauri::Url;
+
+    use crate::app::client_api::mock_api::MockApi;
+    use crate::app::options::Options;
+    use crate::minecraft::auth::MinecraftAccount;
+    use crate::minecraft::crash::CrashReport;
+    use crate::minecraft::java::output::OutputLine;
+    use crate::minecraft::java::JavaDistribution;
+
+    /// Front-end which remembers what the launcher reported, instead of showing it
+    #[derive(Clone, Default)]
+    struct Recorder {
+        labels: Arc<Mutex<Vec<String>>>,
+        logs: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl LauncherFrontend for Recorder {
+        fn on_output(&self, _line: &OutputLine) -> Result<()> {
+            Ok(())
+        }
+
+        fn on_progress(&self, progress_update: ProgressUpdate) -> Result<()> {
+            if let ProgressUpdate::SetLabel(label) = progress_update {
+                self.labels.lock().unwrap().push(label);
+            }
+            Ok(())
+        }
+
+        fn on_log(&self, msg: &str) -> Result<()> {
+            self.logs.lock().unwrap().push(msg.to_string());
+            Ok(())
+        }
+
+        fn on_process_started(&self, _pid: Option<u32>) {}
+
+        fn on_crash(&self, _report: &CrashReport) {}
+
+        fn hide_window(&self) {}
+
+        async fn show_download_page(&self, url: Url) -> Result<String> {
+            bail!("unexpected download page {}", url)
+        }
+    }
+
+    fn launcher_data(recorder: &Recorder) -> LauncherData<Recorder> {
+        let (_, terminator) = tokio::sync::oneshot::channel();
+        LauncherData {
+            data: Box::new(recorder.clone()),
+            terminator,
+        }
+    }
+
+    /// Start parameters of an offline account with its own data directory, connected to the mock API
+    async fn start_parameter(api: &MockApi, name: &str) -> StartParameter {
+        let data = std::env::temp_dir().join(format!(
+            "liquidlauncher-prelaunch-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&data);
+
+        let mut options = Options::default();
+        options.start_options.minecraft_account =
+            Some(MinecraftAccount::auth_offline("Tester".to_string()).await);
+        options.start_options.custom_data_path = data.to_string_lossy().to_string();
+        options.start_options.java_distribution = DistributionSelection::Manual(JavaDistribution::Temurin);
+
+        StartParameter::from_options(Client::new(&api.url(), String::new()), options)
+            .await
+            .unwrap()
+    }
+
+    fn version_profile() -> VersionProfile {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("version_1.21.4.json");
+        serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn dry_run_uses_cached_build_offline() {
+        let api = MockApi::start();
+        let mut parameter = start_parameter(&api, "cached").await;
+        let recorder = Recorder::default();
+
+        let manifest = load_launch_manifest(2, &parameter).await.unwrap();
+        BuildCache::new(&parameter.data_directory(), 2)
+            .store_version_profile(&version_profile())
+            .await
+            .unwrap();
+
+        parameter.offline_mode = true;
+        let plan = dry_run(manifest, &parameter, vec![], &launcher_data(&recorder))
+            .await
+            .unwrap();
+
+        assert_eq!(api.requests(), vec!["/api/v1/version/launch/2"]);
+        assert_eq!(*recorder.labels.lock().unwrap(), vec!["Loading cached version profile..."]);
+        assert_eq!(plan.version_id, "1.21.4");
+        assert_eq!(plan.mods.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), vec!["Fabric API"]);
+        // Offline, the launch manifest comes from the cache which the online request filled
+        assert!(load_launch_manifest(2, &parameter).await.is_ok());
+        assert_eq!(api.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn dry_run_fails_offline_without_cached_build() {
+        let api = MockApi::start();
+        let mut parameter = start_parameter(&api, "uncached").await;
+        let recorder = Recorder::default();
+
+        let manifest = load_launch_manifest(2, &parameter).await.unwrap();
+
+        parameter.offline_mode = true;
+        let error = dry_run(manifest, &parameter, vec![], &launcher_data(&recorder))
+            .await
+            .err()
+            .unwrap();
+
+        assert!(error.to_string().contains("Build 2 is not available offline"));
+        assert_eq!(*recorder.labels.lock().unwrap(), vec!["Loading cached version profile..."]);
+        assert!(recorder.logs.lock().unwrap().is_empty());
+    }
+}
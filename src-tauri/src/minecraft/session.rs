@@ -0,0 +1,309 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::app::client_api::{Build, LoaderMod};
+use crate::utils::{lock_file, HashAlgorithm, Hasher};
+
+const HISTORY_FILE: &str = "sessions.json";
+
+/// Sessions kept in the history, the oldest ones are dropped first
+const HISTORY_LIMIT: usize = 500;
+
+/// Windows exit code of a process that was killed forcefully
+const FORCEFULLY_KILLED: i32 = -1073740791;
+
+/// SIGKILL and SIGTERM, sent by the OOM killer, `kill` or the task manager of the desktop
+const KILL_SIGNALS: [i32; 2] = [9, 15];
+
+/// Concurrent instances finish independently, but share the history file
+static HISTORY_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Locked while the history is accessed, as the launcher window and the command line may run at the same time
+const LOCK_FILE: &str = "sessions.json.lock";
+
+/// How a game process ended, as reported by [crate::minecraft::java::JavaRuntime::handle_io]
+pub struct SessionExit {
+    /// None if the process was terminated by a signal or the launcher
+    pub code: Option<i32>,
+    /// Signal which ended the process on Unix, the exit code is None then
+    pub signal: Option<i32>,
+    /// Terminated by the user from the launcher
+    pub terminated: bool,
+    /// Last lines the game wrote to stderr, oldest first
+    pub stderr_tail: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Termination {
+    Normal,
+    Crash,
+    UserTerminated,
+    /// Killed forcefully from outside, e.g. through the task manager
+    Killed,
+}
+
+impl SessionExit {
+    pub fn termination(&self) -> Termination {
+        match self.code {
+            _ if self.terminated => Termination::UserTerminated,
+            Some(0) => Termination::Normal,
+            Some(FORCEFULLY_KILLED) => Termination::Killed,
+            None if self.signal.is_some_and(|signal| KILL_SIGNALS.contains(&signal)) => Termination::Killed,
+            // Includes processes ended by any other signal, like a segmentation fault or an abort
+            _ => Termination::Crash,
+        }
+    }
+}
+
+/// A single launch of the game, from the start of the process until it exited
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecord {
+    pub build_id: u32,
+    pub branch: String,
+    pub commit_id: String,
+    pub mc_version: String,
+    pub lb_version: String,
+    pub mods: Vec<String>,
+    /// SHA256 of the memory setting and the custom JVM arguments, to group sessions without storing the arguments
+    pub jvm_args_hash: String,
+    pub java: Option<PathBuf>,
+    /// Unix timestamps
+    pub started_at: u64,
+    pub ended_at: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub termination: Option<Termination>,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+impl SessionRecord {
+    pub fn new(build: &Build, mods: &[LoaderMod], jvm_args: &[String], memory: u64) -> Self {
+        let mut hasher = Hasher::new(HashAlgorithm::Sha256);
+        hasher.update(memory.to_string().as_bytes());
+        for arg in jvm_args {
+            hasher.update(b"\0");
+            hasher.update(arg.as_bytes());
+        }
+
+        Self {
+            build_id: build.build_id,
+            branch: build.branch.clone(),
+            commit_id: build.commit_id.clone(),
+            mc_version: build.mc_version.clone(),
+            lb_version: build.lb_version.clone(),
+            mods: mods.iter().map(|loader_mod| loader_mod.name.clone()).collect(),
+            jvm_args_hash: hasher.finalize(),
+            java: None,
+            started_at: now(),
+            ended_at: None,
+            exit_code: None,
+            termination: None,
+        }
+    }
+
+    /// Marks the start of the game process, downloads before are not part of the session
+    pub fn start(&mut self, java: &Path) {
+        self.java = Some(java.to_path_buf());
+        self.started_at = now();
+    }
+
+    pub fn finish(&mut self, exit: &SessionExit) {
+        self.ended_at = Some(now());
+        self.exit_code = exit.code;
+        self.termination = Some(exit.termination());
+    }
+}
+
+/// History of the sessions launched from a data directory
+pub struct SessionHistory {
+    path: PathBuf,
+}
+
+impl SessionHistory {
+    pub fn new(data: &Path) -> Self {
+        Self {
+            path: data.join(HISTORY_FILE),
+        }
+    }
+
+    /// Loads the sessions, oldest first
+    pub async fn load(&self) -> Result<Vec<SessionRecord>> {
+        let _lock = HISTORY_LOCK.lock().await;
+        let _file_lock = lock_file(&self.path.with_file_name(LOCK_FILE)).await?;
+        self.read().await
+    }
+
+    pub async fn append(&self, record: &SessionRecord) -> Result<()> {
+        let _lock = HISTORY_LOCK.lock().await;
+        let _file_lock = lock_file(&self.path.with_file_name(LOCK_FILE)).await?;
+
+        // A corrupted history is started over, but kept aside instead of being lost
+        let mut records = match self.read().await {
+            Ok(records) => records,
+            Err(e) => {
+                warn!("Session history is unreadable, starting over: {:?}", e);
+                self.back_up().await?;
+                vec![]
+            }
+        };
+        records.push(record.clone());
+        if records.len() > HISTORY_LIMIT {
+            records.drain(..records.len() - HISTORY_LIMIT);
+        }
+
+        // Written next to the history and renamed, so it is never left half written
+        let temporary = self.path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_vec(&records)?)
+            .await
+            .with_context(|| format!("Failed to write {}", temporary.display()))?;
+        fs::rename(&temporary, &self.path)
+            .await
+            .with_context(|| format!("Failed to replace {}", self.path.display()))
+    }
+
+    pub async fn clear(&self) -> Result<()> {
+        let _lock = HISTORY_LOCK.lock().await;
+        let _file_lock = lock_file(&self.path.with_file_name(LOCK_FILE)).await?;
+
+        if self.path.exists() {
+            fs::remove_file(&self.path).await?;
+        }
+        Ok(())
+    }
+
+    async fn back_up(&self) -> Result<()> {
+        let backup = self.path.with_extension(format!("json.{}.bak", now()));
+        fs::rename(&self.path, &backup)
+            .await
+            .with_context(|| format!("Failed to back up {}", self.path.display()))?;
+
+        warn!("Kept the unreadable session history as {}", backup.display());
+        Ok(())
+    }
+
+    async fn read(&self) -> Result<Vec<SessionRecord>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+
+        let content = fs::read(&self.path)
+            .await
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        serde_json::from_slice(&content)
+            .with_context(|| format!("Failed to parse {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exit(code: Option<i32>, terminated: bool) -> SessionExit {
+        SessionExit { code, signal: None, terminated, stderr_tail: vec![] }
+    }
+
+    fn signaled(signal: i32) -> SessionExit {
+        SessionExit { code: None, signal: Some(signal), terminated: false, stderr_tail: vec![] }
+    }
+
+    #[test]
+    fn termination_of_exit_codes() {
+        assert_eq!(exit(Some(0), false).termination(), Termination::Normal);
+        assert_eq!(exit(Some(1), false).termination(), Termination::Crash);
+        assert_eq!(exit(Some(-1), false).termination(), Termination::Crash);
+        assert_eq!(exit(Some(FORCEFULLY_KILLED), false).termination(), Termination::Killed);
+    }
+
+    #[test]
+    fn termination_without_exit_code_is_a_crash() {
+        // e.g. a segmentation fault of the JVM
+        assert_eq!(exit(None, false).termination(), Termination::Crash);
+    }
+
+    #[test]
+    fn termination_by_signal() {
+        // SIGKILL and SIGTERM
+        assert_eq!(signaled(9).termination(), Termination::Killed);
+        assert_eq!(signaled(15).termination(), Termination::Killed);
+        // SIGSEGV and SIGABRT
+        assert_eq!(signaled(11).termination(), Termination::Crash);
+        assert_eq!(signaled(6).termination(), Termination::Crash);
+    }
+
+    #[test]
+    fn termination_by_user_takes_precedence() {
+        assert_eq!(exit(None, true).termination(), Termination::UserTerminated);
+        assert_eq!(exit(Some(0), true).termination(), Termination::UserTerminated);
+        assert_eq!(exit(Some(1), true).termination(), Termination::UserTerminated);
+        assert_eq!(exit(Some(FORCEFULLY_KILLED), true).termination(), Termination::UserTerminated);
+    }
+
+    #[tokio::test]
+    async fn unreadable_history_is_backed_up() {
+        let data = std::env::temp_dir().join(format!("liquidlauncher-sessions-{}", std::process::id()));
+        std::fs::create_dir_all(&data).unwrap();
+        std::fs::write(data.join(HISTORY_FILE), "[{").unwrap();
+
+        let history = SessionHistory::new(&data);
+        let record: SessionRecord = serde_json::from_value(serde_json::json!({
+            "buildId": 1,
+            "branch": "nextgen",
+            "commitId": "abc",
+            "mcVersion": "1.21.4",
+            "lbVersion": "0.1.0",
+            "mods": [],
+            "jvmArgsHash": "",
+            "java": null,
+            "startedAt": 0,
+            "endedAt": null,
+            "exitCode": null,
+            "termination": null,
+        }))
+        .unwrap();
+        history.append(&record).await.unwrap();
+
+        assert_eq!(history.load().await.unwrap().len(), 1);
+        let backups = std::fs::read_dir(&data)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".bak"))
+            .map(|entry| std::fs::read_to_string(entry.path()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(backups, vec!["[{"]);
+
+        std::fs::remove_dir_all(&data).unwrap();
+    }
+}
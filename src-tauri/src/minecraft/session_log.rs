@@ -0,0 +1,173 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use serde::Serialize;
+
+use crate::app::client_api::Build;
+use crate::minecraft::java::output::{OutputLine, OutputStream};
+use crate::utils::clean_directory;
+
+/// Session logs older than this are removed before a new session is logged
+const RETENTION_DAYS: u64 = 14;
+
+/// Sessions launched within the same second are told apart by a counter, up to this many
+const MAX_SUFFIX: u32 = 100;
+
+/// Directory of the session logs in the data directory, so they move along with it
+pub fn session_logs_directory(data: &Path) -> PathBuf {
+    data.join("session_logs")
+}
+
+///
+/// Removes session logs which are older than the retention period
+///
+pub fn clean_session_logs(data: &Path) -> Result<()> {
+    let directory = session_logs_directory(data);
+    if !directory.exists() {
+        return Ok(());
+    }
+
+    clean_directory(&directory, RETENTION_DAYS)
+}
+
+/// The output of a single game session, written line by line so nothing is lost if the launcher crashes
+pub struct SessionLog {
+    writer: LineWriter<File>,
+}
+
+impl SessionLog {
+    ///
+    /// Creates the log file of a new session, named by instance, build and time of the launch
+    ///
+    pub fn create(data: &Path, instance: Option<&str>, build: &Build) -> Result<Self> {
+        let directory = session_logs_directory(data);
+        fs::create_dir_all(&directory)?;
+
+        let stem = format!(
+            "{}_{}_{}",
+            sanitize(instance.unwrap_or(&build.branch)),
+            build.build_id,
+            Local::now().format("%Y-%m-%d_%H-%M-%S")
+        );
+
+        // Never truncates the log of another session, which was started within the same second
+        for suffix in 0..MAX_SUFFIX {
+            let name = match suffix {
+                0 => format!("{}.log", stem),
+                _ => format!("{}_{}.log", stem, suffix),
+            };
+            let path = directory.join(name);
+
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    return Ok(Self {
+                        writer: LineWriter::new(file),
+                    })
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e).with_context(|| format!("Failed to create {}", path.display())),
+            }
+        }
+
+        bail!("Too many session logs named {}", stem)
+    }
+
+    pub fn write(&mut self, line: &OutputLine) -> Result<()> {
+        match line.stream {
+            OutputStream::Stderr => writeln!(self.writer, "[STDERR] {}", line.text)?,
+            _ => writeln!(self.writer, "{}", line.text)?,
+        }
+        Ok(())
+    }
+}
+
+/// Keeps names readable in the file system, instance names are chosen by the user
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect()
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLogFile {
+    pub name: String,
+    pub size: u64,
+    /// Unix timestamp of the last write
+    pub modified: u64,
+}
+
+///
+/// Lists the session logs, newest first
+///
+pub fn list_session_logs(data: &Path) -> Result<Vec<SessionLogFile>> {
+    let directory = session_logs_directory(data);
+    if !directory.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut logs = fs::read_dir(&directory)?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|extension| extension == "log"))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+
+            Some(SessionLogFile {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                size: metadata.len(),
+                modified,
+            })
+        })
+        .collect::<Vec<_>>();
+    logs.sort_by(|a, b| b.modified.cmp(&a.modified));
+    Ok(logs)
+}
+
+///
+/// Resolves the path of a session log by its name, names leading out of the directory are rejected
+///
+pub fn session_log_path(data: &Path, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || Path::new(name).file_name().and_then(|file_name| file_name.to_str()) != Some(name) {
+        bail!("Invalid session log name: {}", name);
+    }
+
+    let path = session_logs_directory(data).join(name);
+    if !path.is_file() {
+        bail!("Session log {} does not exist", name);
+    }
+    Ok(path)
+}
+
+pub fn read_session_log(data: &Path, name: &str) -> Result<String> {
+    let content = fs::read(session_log_path(data, name)?)?;
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
@@ -27,37 +27,33 @@ use std::{
 
 use crate::minecraft::launcher::StartParameter;
 use crate::minecraft::progress::{ProgressReceiver, ProgressUpdate};
-use crate::utils::{get_maven_artifact_path, sha1sum};
+use crate::utils::{get_maven_artifact_path, sha1sum, Checksum};
 use crate::{
     error::LauncherError,
-    utils::{download_file_untracked, Architecture},
-    HTTP_CLIENT,
+    utils::{download_file_untracked, get_mirrored, Architecture},
 };
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{
     de::{self, MapAccess, Visitor},
-    Deserialize, Deserializer,
+    Deserialize, Deserializer, Serialize,
 };
 use std::collections::HashSet;
 use tokio::fs;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use void::Void;
 
 // https://launchermeta.mojang.com/mc/game/version_manifest.json
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct VersionManifest {
     pub versions: Vec<ManifestVersion>,
 }
 
 impl VersionManifest {
     pub async fn fetch() -> Result<Self> {
-        let response = HTTP_CLIENT.get("https://piston-meta.mojang.com/mc/game/version_manifest_v2.json")
-            .send()
+        let response = get_mirrored("https://piston-meta.mojang.com/mc/game/version_manifest_v2.json", |request| request)
             .await
-            .context("Connection to https://piston-meta.mojang.com/ failed. Check your internet connection.")?
-            .error_for_status()
-            .context("https://piston-meta.mojang.com/ returned with an error code, try again later!")?;
+            .context("Connection to https://piston-meta.mojang.com/ failed. Check your internet connection.")?;
         let manifest = response.json::<VersionManifest>().await.context(
             "Failed to parse Version Manifest, Mojang Server responded with not valid format.",
         )?;
@@ -66,7 +62,7 @@ impl VersionManifest {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct ManifestVersion {
     pub id: String,
     #[serde(rename = "type")]
@@ -77,7 +73,7 @@ pub struct ManifestVersion {
     pub release_time: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct VersionProfile {
     pub id: String,
     #[serde(rename = "assetIndex")]
@@ -213,7 +209,7 @@ fn merge_libraries(current_libraries: &mut Vec<Library>, parent_libraries: Vec<L
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(untagged)] // TODO: Might guess from minimum_launcher_version just to be sure.
 pub enum ArgumentDeclaration {
     /// V21 describes the new version json used by versions above 1.13.
@@ -307,13 +303,13 @@ impl ArgumentDeclaration {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct V14ArgumentDeclaration {
     #[serde(rename = "minecraftArguments")]
     pub minecraft_arguments: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct V21ArgumentDeclaration {
     pub arguments: Arguments,
 }
@@ -322,13 +318,9 @@ impl VersionProfile {
     pub async fn load(url: &String) -> Result<Self> {
         debug!("Loading version profile from {}", url);
 
-        let version_profile = HTTP_CLIENT
-            .get(url)
-            .send()
+        let version_profile = get_mirrored(url, |request| request)
             .await
             .context(format!("failed to pull version profile from {}", url))?
-            .error_for_status()
-            .context(format!("{} responded with error code.", url))?
             .json::<VersionProfile>()
             .await
             .context(format!("{} responded with not valid format.", url))?;
@@ -340,7 +332,7 @@ impl VersionProfile {
 // Parsing the arguments was pain, please mojang. What in the hell did you do?
 // https://github.com/serde-rs/serde/issues/723 That's why I've done a workaround using vec_argument
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct Arguments {
     #[serde(default)]
     #[serde(deserialize_with = "vec_argument")]
@@ -350,13 +342,13 @@ pub struct Arguments {
     pub jvm: Vec<Argument>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct Argument {
     pub rules: Option<Vec<Rule>>,
     pub value: ArgumentValue,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum ArgumentValue {
     SINGLE(String),
@@ -420,7 +412,7 @@ where
     deserializer.deserialize_any(StringOrStruct(PhantomData))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct AssetIndexLocation {
     pub id: String,
     pub sha1: String,
@@ -445,12 +437,12 @@ impl AssetIndexLocation {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct AssetIndex {
     pub objects: HashMap<String, AssetObject>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct AssetObject {
     pub hash: String,
     pub size: i64,
@@ -471,32 +463,48 @@ impl AssetObject {
 
         let asset_path = asset_folder.join(&self.hash);
 
-        if !asset_path.exists() {
-            progress.progress_update(ProgressUpdate::set_label(format!(
-                "Downloading asset object {}",
-                self.hash
-            )));
-
-            info!("Downloading {}", self.hash);
-            download_file_untracked(
-                &*format!(
-                    "https://resources.download.minecraft.net/{}/{}",
-                    &self.hash[0..2],
-                    &self.hash
-                ),
-                asset_path,
-            )
-            .await?;
-            info!("Downloaded {}", self.hash);
-
-            Ok(true)
-        } else {
-            Ok(false)
+        // Existing objects are only checked by size, hashing all of them on every launch would take too long
+        if asset_path.exists() {
+            if self.size_matches(&asset_path).await {
+                return Ok(false);
+            }
+            warn!("Asset object {} has an unexpected size, downloading it again", self.hash);
         }
+
+        progress.progress_update(ProgressUpdate::set_label(format!(
+            "Downloading asset object {}",
+            self.hash
+        )));
+
+        info!("Downloading {}", self.hash);
+        let hash = download_file_untracked(
+            &*format!(
+                "https://resources.download.minecraft.net/{}/{}",
+                &self.hash[0..2],
+                &self.hash
+            ),
+            &asset_path,
+        )
+        .await?;
+
+        // Objects are named after their SHA1
+        if !Checksum::sha1(&self.hash).matches(&hash) || !self.size_matches(&asset_path).await {
+            fs::remove_file(&asset_path).await?;
+            bail!("Asset object {} is corrupted, got SHA1 {}", self.hash, hash);
+        }
+        info!("Downloaded {}", self.hash);
+
+        Ok(true)
+    }
+
+    async fn size_matches(&self, path: &Path) -> bool {
+        fs::metadata(path)
+            .await
+            .is_ok_and(|metadata| metadata.len() as i64 == self.size)
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct Downloads {
     pub client: Option<Download>,
     pub client_mappings: Option<Download>,
@@ -505,7 +513,7 @@ pub struct Downloads {
     pub windows_server: Option<Download>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct Download {
     pub sha1: String,
     pub size: i64,
@@ -520,7 +528,7 @@ impl Download {
     }
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Library {
     pub name: String,
     pub downloads: Option<LibraryDownloads>,
@@ -570,21 +578,21 @@ impl Library {
     }
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Rule {
     pub action: RuleAction,
     pub os: Option<OsRule>,
     pub features: Option<HashMap<String, bool>>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct OsRule {
     pub name: Option<String>,
     pub version: Option<String>,
     pub arch: Option<Architecture>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub enum RuleAction {
     #[serde(rename = "allow")]
     Allow,
@@ -592,13 +600,13 @@ pub enum RuleAction {
     Disallow,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct LibraryDownloads {
     pub artifact: Option<LibraryArtifact>,
     pub classifiers: Option<HashMap<String, LibraryArtifact>>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct LibraryArtifact {
     pub path: String,
     pub sha1: String,
@@ -627,11 +635,8 @@ impl From<&LibraryArtifact> for LibraryDownloadInfo {
 
 impl LibraryDownloadInfo {
     async fn fetch_sha1(&self) -> Result<String> {
-        HTTP_CLIENT
-            .get(&format!("{}{}", &self.url, ".sha1"))
-            .send()
+        get_mirrored(&format!("{}{}", &self.url, ".sha1"), |request| request)
             .await?
-            .error_for_status()?
             .text()
             .await
             .context("Failed to fetch SHA1 of library")
@@ -679,7 +684,7 @@ impl LibraryDownloadInfo {
         // Check if library already exists
         if library_path.exists() {
             // Check if sha1 matches
-            let hash = sha1sum(&library_path).context("Failed to calculate SHA1 of library")?;
+            let hash = sha1sum(&library_path).await.context("Failed to calculate SHA1 of library")?;
 
             if let Some(sha1) = &sha1 {
                 if hash == *sha1 {
@@ -720,13 +725,12 @@ impl LibraryDownloadInfo {
             &library_path.display()
         ));
 
-        download_file_untracked(&self.url, &library_path)
+        let hash = download_file_untracked(&self.url, &library_path)
             .await
             .context("Failed to download library")?;
 
         // After downloading, check SHA1
         if let Some(sha1) = &sha1 {
-            let hash = sha1sum(&library_path).context("Failed to calculate SHA1 of library")?;
             if hash != *sha1 {
                 anyhow::bail!("SHA1 of library {} does not match.", name);
             }
@@ -736,7 +740,154 @@ impl LibraryDownloadInfo {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct Logging {
-    // TODO: Add logging configuration
+    pub client: Option<LoggingConfiguration>,
+}
+
+/// Log4j configuration file and the JVM argument which points the game to it
+#[derive(Deserialize, Serialize)]
+pub struct LoggingConfiguration {
+    /// e.g. `-Dlog4j.configurationFile=${path}`
+    pub argument: String,
+    pub file: LoggingFile,
+    /// e.g. `log4j2-xml`
+    #[serde(rename = "type")]
+    pub config_type: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct LoggingFile {
+    pub id: String,
+    pub sha1: String,
+    pub size: i64,
+    pub url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V21_PROFILE: &str = r#"{
+        "id": "1.21.4",
+        "assets": "19",
+        "libraries": [
+            {
+                "name": "org.lwjgl:lwjgl:3.3.3:natives-linux",
+                "rules": [{ "action": "allow", "os": { "name": "linux" } }]
+            }
+        ],
+        "mainClass": "net.minecraft.client.main.Main",
+        "type": "release",
+        "arguments": {
+            "game": [
+                "--username",
+                "${auth_player_name}",
+                {
+                    "rules": [{ "action": "allow", "features": { "is_demo_user": true } }],
+                    "value": "--demo"
+                }
+            ],
+            "jvm": [
+                {
+                    "rules": [{ "action": "allow", "os": { "name": "osx" } }],
+                    "value": ["-XstartOnFirstThread"]
+                },
+                "-cp",
+                "${classpath}"
+            ]
+        }
+    }"#;
+
+    const V14_PROFILE: &str = r#"{
+        "id": "1.8.9",
+        "libraries": [],
+        "type": "release",
+        "minecraftArguments": "--username ${auth_player_name} --version ${version_name}"
+    }"#;
+
+    /// Serializes like the cache does and parses the result again
+    fn round_trip<T: Serialize + for<'de> Deserialize<'de>>(value: &T) -> T {
+        let bytes = serde_json::to_vec(value).unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn single(argument: &Argument) -> &str {
+        match &argument.value {
+            ArgumentValue::SINGLE(value) => value,
+            ArgumentValue::VEC(_) => panic!("expected a single value"),
+        }
+    }
+
+    #[test]
+    fn v21_profile_survives_cache_round_trip() {
+        let profile = serde_json::from_str::<VersionProfile>(V21_PROFILE).unwrap();
+        let profile = round_trip(&profile);
+
+        assert_eq!(profile.id, "1.21.4");
+        assert_eq!(profile.main_class.as_deref(), Some("net.minecraft.client.main.Main"));
+        assert_eq!(profile.libraries.len(), 1);
+        assert_eq!(profile.libraries[0].rules.len(), 1);
+
+        let ArgumentDeclaration::V21(declaration) = &profile.arguments else {
+            panic!("expected a V21 argument declaration");
+        };
+        let arguments = &declaration.arguments;
+
+        assert_eq!(arguments.game.len(), 3);
+        assert_eq!(single(&arguments.game[0]), "--username");
+        assert!(arguments.game[0].rules.is_none());
+        assert_eq!(single(&arguments.game[2]), "--demo");
+        let rules = arguments.game[2].rules.as_ref().unwrap();
+        assert_eq!(rules[0].features.as_ref().unwrap().get("is_demo_user"), Some(&true));
+
+        assert_eq!(arguments.jvm.len(), 3);
+        match &arguments.jvm[0].value {
+            ArgumentValue::VEC(values) => assert_eq!(values, &["-XstartOnFirstThread"]),
+            ArgumentValue::SINGLE(_) => panic!("expected a list of values"),
+        }
+        let os = arguments.jvm[0].rules.as_ref().unwrap()[0].os.as_ref().unwrap();
+        assert_eq!(os.name.as_deref(), Some("osx"));
+        assert_eq!(single(&arguments.jvm[2]), "${classpath}");
+    }
+
+    #[test]
+    fn v14_profile_survives_cache_round_trip() {
+        let profile = serde_json::from_str::<VersionProfile>(V14_PROFILE).unwrap();
+        let profile = round_trip(&profile);
+
+        assert_eq!(profile.id, "1.8.9");
+        let ArgumentDeclaration::V14(declaration) = &profile.arguments else {
+            panic!("expected a V14 argument declaration");
+        };
+        assert_eq!(
+            declaration.minecraft_arguments.as_deref(),
+            Some("--username ${auth_player_name} --version ${version_name}")
+        );
+    }
+
+    #[test]
+    fn manifest_survives_cache_round_trip() {
+        let manifest = serde_json::from_str::<VersionManifest>(
+            r#"{
+                "latest": { "release": "1.21.4", "snapshot": "1.21.4" },
+                "versions": [{
+                    "id": "1.21.4",
+                    "type": "release",
+                    "url": "https://piston-meta.mojang.com/v1/packages/1.21.4.json",
+                    "time": "2024-12-03T10:24:48+00:00",
+                    "releaseTime": "2024-12-03T10:12:57+00:00",
+                    "sha1": "a3bcba436caa849622fd7e1e5b89489ed6c9ac63"
+                }]
+            }"#,
+        )
+        .unwrap();
+        let manifest = round_trip(&manifest);
+
+        assert_eq!(manifest.versions.len(), 1);
+        let version = &manifest.versions[0];
+        assert_eq!(version.id, "1.21.4");
+        assert_eq!(version.version_type, "release");
+        assert_eq!(version.release_time, "2024-12-03T10:12:57+00:00");
+    }
 }
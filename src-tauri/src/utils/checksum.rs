@@ -17,11 +17,129 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use anyhow::Result;
-use sha1::{Digest, Sha1};
-use std::path::PathBuf;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
-pub fn sha1sum(path: &PathBuf) -> Result<String> {
-    let hash = Sha1::digest(std::fs::read(path)?);
-    Ok(base16ct::lower::encode_string(&hash))
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::Sha256;
+
+/// Hash algorithms which files are verified with
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    #[serde(rename = "sha1")]
+    Sha1,
+    #[serde(rename = "sha256")]
+    Sha256,
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            HashAlgorithm::Sha1 => "SHA1",
+            HashAlgorithm::Sha256 => "SHA256",
+        })
+    }
+}
+
+/// Incrementally hashes data with one of the [HashAlgorithm]s
+pub enum Hasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha1 => Hasher::Sha1(sha1::Digest::new()),
+            HashAlgorithm::Sha256 => Hasher::Sha256(sha2::Digest::new()),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha1(hasher) => sha1::Digest::update(hasher, data),
+            Hasher::Sha256(hasher) => sha2::Digest::update(hasher, data),
+        }
+    }
+
+    /// Returns the lowercase hex digest
+    pub fn finalize(self) -> String {
+        match self {
+            Hasher::Sha1(hasher) => base16ct::lower::encode_string(&sha1::Digest::finalize(hasher)),
+            Hasher::Sha256(hasher) => {
+                base16ct::lower::encode_string(&sha2::Digest::finalize(hasher))
+            }
+        }
+    }
+}
+
+///
+/// Expected hash of a file, e.g. published by the API or the vendor of an artifact
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: HashAlgorithm,
+    pub value: String,
+}
+
+impl Checksum {
+    pub fn sha1(value: impl Into<String>) -> Self {
+        Self { algorithm: HashAlgorithm::Sha1, value: value.into() }
+    }
+
+    pub fn sha256(value: impl Into<String>) -> Self {
+        Self { algorithm: HashAlgorithm::Sha256, value: value.into() }
+    }
+
+    /// Returns true if the hex digest is the expected one
+    pub fn matches(&self, digest: &str) -> bool {
+        self.value.trim().eq_ignore_ascii_case(digest)
+    }
+
+    /// Hashes the file and fails if it does not match
+    pub async fn verify(&self, path: &Path) -> Result<()> {
+        let digest = hash_file_async(path, self.algorithm).await?;
+
+        if !self.matches(&digest) {
+            bail!(
+                "{} of {} does not match. Expected {}, got {}",
+                self.algorithm,
+                path.display(),
+                self.value,
+                digest
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Hashes the file without reading it into memory at once
+pub fn hash_file(path: &Path, algorithm: HashAlgorithm) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Same as [hash_file], but on the blocking thread pool, so hashing large files doesn't stall other tasks
+pub async fn hash_file_async(path: &Path, algorithm: HashAlgorithm) -> Result<String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || hash_file(&path, algorithm)).await?
+}
+
+pub async fn sha1sum(path: &PathBuf) -> Result<String> {
+    hash_file_async(path, HashAlgorithm::Sha1).await
 }
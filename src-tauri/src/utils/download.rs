@@ -17,74 +17,375 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::path::Path;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::error::map_into_connection_error;
-use crate::HTTP_CLIENT;
-use anyhow::Result;
+use crate::utils::{get_mirrored, HashAlgorithm, Hasher};
+use anyhow::{bail, Context, Result};
 use backon::{ExponentialBuilder, Retryable};
+use reqwest::header::{
+    HeaderName, ACCEPT_RANGES, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE,
+};
+use reqwest::{Response, StatusCode};
 use tokio::fs;
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
 use tracing::{debug, warn};
 
 static DOWNLOADED_BYTES: AtomicU64 = AtomicU64::new(0);
+static DOWNLOAD_ID: AtomicU64 = AtomicU64::new(0);
 
 pub fn downloaded_bytes() -> u64 {
     DOWNLOADED_BYTES.load(Ordering::Relaxed)
 }
 
-/// Download a file using HTTP_CLIENT without any progress tracking
-pub async fn download_file_untracked(url: &str, path: impl AsRef<Path>) -> Result<()> {
-    let path = path.as_ref().to_owned();
-    let mut response = HTTP_CLIENT.get(url).send().await?.error_for_status()?;
+/// Returns the path a download is written to before it is moved to its destination
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut file_name = path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_else(OsString::new);
+    file_name.push(".part");
+    path.with_file_name(file_name)
+}
 
-    let mut content = Vec::with_capacity(response.content_length().unwrap_or(0) as usize);
-    while let Some(chunk) = response.chunk().await? {
-        DOWNLOADED_BYTES.fetch_add(chunk.len() as u64, Ordering::Relaxed);
-        content.extend_from_slice(&chunk);
-    }
+/// Returns a path next to the destination which no other download writes to,
+/// the same file is downloaded concurrently by duplicate assets or instances launched at once
+fn download_partial_path(path: &Path) -> PathBuf {
+    let mut file_name = path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_else(OsString::new);
+    file_name.push(format!(
+        ".{}-{}.part",
+        std::process::id(),
+        DOWNLOAD_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(file_name)
+}
 
-    fs::write(path, content).await?;
-    Ok(())
+/// Returns the path of the validator (ETag or Last-Modified) which a partial download is resumed with
+fn validator_path(part_path: &Path) -> PathBuf {
+    let mut file_name = part_path.as_os_str().to_os_string();
+    file_name.push(".meta");
+    PathBuf::from(file_name)
 }
 
-pub async fn download_file<F>(url: &str, on_progress: F) -> Result<Vec<u8>>
+/// Download a file using HTTP_CLIENT without any progress tracking, returns the SHA1 of the file
+pub async fn download_file_untracked(url: &str, path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    let part_path = download_partial_path(path);
+    let hash = fetch_to_file(url, path, &part_path, |_, _| {}).await;
+    if hash.is_err() {
+        discard_partial(&part_path, &validator_path(&part_path)).await;
+    }
+    hash
+}
+
+///
+/// Downloads a file to the given path and returns its SHA1
+///
+/// The response is streamed into a partial file next to the destination, which is only
+/// renamed to the destination once the download has completed. Retries continue
+/// the partial file of this download where it stopped if the server supports range requests.
+///
+pub async fn download_file<F>(url: &str, path: impl AsRef<Path>, on_progress: F) -> Result<String>
 where
     F: Fn(u64, u64),
 {
     debug!("Downloading file {:?}", url);
 
-    let mut response = (|| async {
-        HTTP_CLIENT
-            .get(url.trim())
-            .send()
-            .await?
-            .error_for_status()
-    }).retry(ExponentialBuilder::default())
+    let path = path.as_ref();
+    let part_path = download_partial_path(path);
+    let hash = (|| async { fetch_to_file(url.trim(), path, &part_path, &on_progress).await })
+        .retry(ExponentialBuilder::default())
         .notify(|err, dur| {
             warn!("Failed to download file {}. Retrying in {:?}. Error: {}", url, dur, err);
         })
+        .await;
+
+    // The partial file is unique to this download, nothing else can resume it
+    let hash = match hash {
+        Ok(hash) => hash,
+        Err(err) => {
+            discard_partial(&part_path, &validator_path(&part_path)).await;
+            return Err(err);
+        }
+    };
+
+    debug!("Downloaded file");
+    Ok(hash)
+}
+
+///
+/// Requests the URL and streams the response body to the path while hashing it
+///
+/// A partial file left behind by an earlier attempt is resumed with a `Range` request,
+/// `If-Range` makes the server send the whole file instead if it has changed since.
+///
+async fn fetch_to_file<F>(url: &str, path: &Path, part_path: &Path, on_progress: F) -> Result<String>
+where
+    F: Fn(u64, u64),
+{
+    let validator_path = validator_path(part_path);
+
+    let mut resume = resume_point(part_path, &validator_path).await;
+    let mut response = request(url, resume.as_ref()).await;
+
+    // The partial file is larger than the remote file, it can only be started over
+    if resume.is_some() && response.as_ref().is_err_and(is_range_not_satisfiable) {
+        warn!("Discarding partial download of {}", url);
+        discard_partial(part_path, &validator_path).await;
+        resume = None;
+        response = request(url, None).await;
+    }
+    let mut response = response.map_err(map_into_connection_error)?;
+
+    let offset = match (&resume, response.status()) {
+        (Some((offset, _)), StatusCode::PARTIAL_CONTENT) => {
+            if content_range_start(&response) != Some(*offset) {
+                discard_partial(part_path, &validator_path).await;
+                bail!("Server responded with an unexpected range for {}", url);
+            }
+
+            debug!("Resuming download of {} at byte {}", url, offset);
+            *offset
+        }
+        _ => {
+            // Remember how to resume this download, if the server supports it
+            match resume_validator(&response) {
+                Some(validator) => fs::write(&validator_path, validator).await?,
+                None => {
+                    let _ = fs::remove_file(&validator_path).await;
+                }
+            }
+            0
+        }
+    };
+
+    let mut hasher = Hasher::new(HashAlgorithm::Sha1);
+    let file = if offset > 0 {
+        hash_partial(part_path, &mut hasher).await?;
+        OpenOptions::new().append(true).open(part_path).await
+    } else {
+        fs::File::create(part_path).await
+    }
+    .with_context(|| format!("Failed to open {}", part_path.display()))?;
+    let mut file = BufWriter::new(file);
+
+    let max_len = offset + response.content_length().unwrap_or(0);
+    let mut curr_len = offset;
+
+    on_progress(curr_len, max_len);
+
+    let streamed = async {
+        while let Some(data) = response.chunk().await
+            .map_err(|e| map_into_connection_error(e.into()))? {
+            DOWNLOADED_BYTES.fetch_add(data.len() as u64, Ordering::Relaxed);
+            hasher.update(&data);
+            file.write_all(&data).await?;
+            curr_len += data.len() as u64;
+            on_progress(curr_len, max_len);
+        }
+        anyhow::Ok(())
+    }
+    .await;
+
+    // Flush in any case, so a resumed download continues right after the received bytes
+    let flushed = file.flush().await;
+    let written = match streamed.and(flushed.map_err(Into::into)) {
+        Ok(()) => file.into_inner().sync_all().await.map_err(Into::into),
+        Err(err) => Err(err),
+    };
+
+    if let Err(err) = written {
+        if validator_path.exists() {
+            debug!("Keeping partial download of {} at byte {}", url, curr_len);
+        } else {
+            let _ = fs::remove_file(part_path).await;
+        }
+        return Err(err);
+    }
+
+    fs::rename(part_path, path)
         .await
-        .map_err(|e| map_into_connection_error(e.into()))?;
+        .with_context(|| format!("Failed to move download to {}", path.display()))?;
+    let _ = fs::remove_file(&validator_path).await;
+
+    Ok(hasher.finalize())
+}
+
+/// Sends the request, asking only for the missing bytes of a partial file
+async fn request(url: &str, resume: Option<&(u64, String)>) -> Result<Response> {
+    get_mirrored(url, |request| match resume {
+        Some((offset, validator)) => request
+            .header(RANGE, format!("bytes={}-", offset))
+            .header(IF_RANGE, validator.as_str()),
+        None => request,
+    })
+    .await
+}
 
-    debug!("Response received from url");
+/// Returns the length and validator of a partial file which can be resumed
+async fn resume_point(part_path: &Path, validator_path: &Path) -> Option<(u64, String)> {
+    let length = fs::metadata(part_path).await.ok()?.len();
+    let validator = fs::read_to_string(validator_path).await.ok()?;
 
-    let max_len = response.content_length().unwrap_or(0);
-    let mut output = Vec::with_capacity(max_len as usize);
-    let mut curr_len = 0;
+    (length > 0 && !validator.is_empty()).then_some((length, validator))
+}
 
-    on_progress(0, max_len);
+/// Returns the validator to resume the response with, if the server accepts byte ranges
+fn resume_validator(response: &Response) -> Option<String> {
+    let header = |name: HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
 
-    debug!("Reading data from response chunk...");
-    while let Some(data) = response.chunk().await
-        .map_err(|e| map_into_connection_error(e.into()))? {
-        DOWNLOADED_BYTES.fetch_add(data.len() as u64, Ordering::Relaxed);
-        output.extend_from_slice(&data);
-        curr_len += data.len();
-        on_progress(curr_len as u64, max_len);
+    if !header(ACCEPT_RANGES).is_some_and(|value| value.eq_ignore_ascii_case("bytes")) {
+        return None;
     }
 
-    debug!("Downloaded file");
-    Ok(output)
+    // If-Range only accepts strong validators
+    header(ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(LAST_MODIFIED))
+        .map(str::to_owned)
 }
 
+/// Returns the first byte of a `Content-Range: bytes <start>-<end>/<length>` header
+fn content_range_start(response: &Response) -> Option<u64> {
+    response
+        .headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn is_range_not_satisfiable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(|err| err.status())
+        .is_some_and(|status| status == StatusCode::RANGE_NOT_SATISFIABLE)
+}
+
+/// Feeds the bytes which were already downloaded into the hasher
+async fn hash_partial(part_path: &Path, hasher: &mut Hasher) -> Result<()> {
+    let mut file = fs::File::open(part_path).await?;
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(())
+}
+
+async fn discard_partial(part_path: &Path, validator_path: &Path) {
+    let _ = fs::remove_file(part_path).await;
+    let _ = fs::remove_file(validator_path).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::app::client_api::mock_api::MockApi;
+
+    const CONTENT: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    const ETAG: &str = "\"v2\"";
+    const FILE: &str = "/files/library.jar";
+
+    fn sha1(content: &[u8]) -> String {
+        let mut hasher = Hasher::new(HashAlgorithm::Sha1);
+        hasher.update(content);
+        hasher.finalize()
+    }
+
+    /// Returns the destination of a download in a new directory
+    fn destination(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "liquidlauncher-download-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory.join("library.jar")
+    }
+
+    fn file_names(path: &Path) -> Vec<String> {
+        std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn interrupted_download_is_resumed() {
+        let api = MockApi::builder()
+            .file(FILE, CONTENT, ETAG)
+            .interrupt(FILE, 10)
+            .start();
+        let path = destination("resume");
+
+        let hash = download_file(&format!("{}{}", api.url(), FILE), &path, |_, _| {})
+            .await
+            .unwrap();
+
+        assert_eq!(hash, sha1(CONTENT));
+        assert_eq!(std::fs::read(&path).unwrap(), CONTENT);
+        assert_eq!(api.ranges(), vec!["bytes=10-"]);
+        assert_eq!(file_names(&path), vec!["library.jar"]);
+    }
+
+    #[tokio::test]
+    async fn changed_file_is_downloaded_again() {
+        let api = MockApi::builder().file(FILE, CONTENT, ETAG).start();
+        let path = destination("changed");
+        let part_path = download_partial_path(&path);
+        std::fs::write(&part_path, b"stale").unwrap();
+        std::fs::write(validator_path(&part_path), "\"v1\"").unwrap();
+
+        let hash = fetch_to_file(&format!("{}{}", api.url(), FILE), &path, &part_path, |_, _| {})
+            .await
+            .unwrap();
+
+        // If-Range does not match, so the whole file is sent instead of the range
+        assert_eq!(api.ranges(), vec!["bytes=5-"]);
+        assert_eq!(hash, sha1(CONTENT));
+        assert_eq!(std::fs::read(&path).unwrap(), CONTENT);
+        assert_eq!(file_names(&path), vec!["library.jar"]);
+    }
+
+    #[tokio::test]
+    async fn oversized_partial_file_is_discarded() {
+        let api = MockApi::builder().file(FILE, CONTENT, ETAG).start();
+        let path = destination("oversized");
+        let part_path = download_partial_path(&path);
+        std::fs::write(&part_path, [CONTENT, &b"trailing"[..]].concat()).unwrap();
+        std::fs::write(validator_path(&part_path), ETAG).unwrap();
+
+        let hash = fetch_to_file(&format!("{}{}", api.url(), FILE), &path, &part_path, |_, _| {})
+            .await
+            .unwrap();
+
+        // Answered with 416, then requested again from the start
+        assert_eq!(api.ranges(), vec![format!("bytes={}-", CONTENT.len() + 8)]);
+        assert_eq!(api.requests(), vec![FILE, FILE]);
+        assert_eq!(hash, sha1(CONTENT));
+        assert_eq!(std::fs::read(&path).unwrap(), CONTENT);
+        assert_eq!(file_names(&path), vec!["library.jar"]);
+    }
+}
@@ -17,15 +17,17 @@
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use async_compression::tokio::bufread::GzipDecoder;
 use async_zip::base::read::seek::ZipFileReader;
 use std::path::{Path, PathBuf};
-use tokio::fs::{create_dir_all, OpenOptions};
+use tokio::fs::{create_dir_all, rename, remove_file, File, OpenOptions};
 use tokio::io;
-use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, BufReader};
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWriteExt, BufReader};
 use tokio_util::compat::FuturesAsyncReadCompatExt;
 
+use crate::utils::partial_path;
+
 /// Extracts everything from the ZIP archive to the output directory
 ///
 /// Taken from https://github.com/Majored/rs-async-zip/blob/main/examples/file_extraction.rs
@@ -72,15 +74,52 @@ where
     Ok(())
 }
 
-pub async fn tar_gz_extract<R>(archive: R, out_dir: &Path) -> Result<()>
+///
+/// Extracts the first entry of the ZIP archive matching the predicate to the output file
+///
+/// Returns false if there is no matching entry in the archive.
+///
+pub async fn zip_extract_entry<R, P>(archive: R, predicate: P, out_file: &Path) -> Result<bool>
 where
     R: AsyncRead + AsyncSeek + Unpin,
+    P: Fn(&str) -> bool,
 {
-    let mut decoder = GzipDecoder::new(BufReader::new(archive));
-    let mut decoded_data: Vec<u8> = vec![];
-    decoder.read_to_end(&mut decoded_data).await?;
+    let mut reader = ZipFileReader::with_tokio(BufReader::new(archive)).await?;
+    let Some(index) = reader
+        .file()
+        .entries()
+        .iter()
+        .position(|entry| entry.filename().as_str().is_ok_and(&predicate))
+    else {
+        return Ok(false);
+    };
+    let crc = reader.file().entries()[index].crc32();
+
+    let part_path = partial_path(out_file);
+    let mut writer = File::create(&part_path)
+        .await
+        .context("Failed to create extracted file")?;
+    let mut entry_reader = reader.reader_with_entry(index).await?.compat();
+    io::copy(&mut entry_reader, &mut writer).await?;
+    writer.flush().await?;
+    drop(writer);
 
-    let mut ar = tokio_tar::Archive::new(&decoded_data[..]);
+    if entry_reader.get_mut().compute_hash() != crc {
+        remove_file(&part_path).await?;
+        bail!("CRC32 of the extracted entry does not match");
+    }
+
+    rename(&part_path, out_file).await?;
+    Ok(true)
+}
+
+/// Extracts everything from the gzipped TAR archive to the output directory, while decompressing it
+pub async fn tar_gz_extract<R>(archive: R, out_dir: &Path) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let decoder = GzipDecoder::new(BufReader::new(archive));
+    let mut ar = tokio_tar::Archive::new(decoder);
     ar.unpack(out_dir).await?;
     Ok(())
 }
@@ -0,0 +1,234 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use reqwest::{Method, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::HTTP_CLIENT;
+
+static MIRROR_RULES: Lazy<RwLock<Vec<MirrorRule>>> = Lazy::new(|| RwLock::new(vec![]));
+
+/// Mirrors which failed during this session and are skipped from then on
+static FAILED_MIRRORS: Lazy<RwLock<HashSet<String>>> = Lazy::new(|| RwLock::new(HashSet::new()));
+
+///
+/// Rewrites every URL starting with [MirrorRule::from] to start with [MirrorRule::to] instead,
+/// e.g. `https://libraries.minecraft.net` to `https://bmclapi2.bangbang93.com/maven`
+///
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MirrorRule {
+    #[serde(rename = "from")]
+    pub from: String,
+    #[serde(rename = "to")]
+    pub to: String,
+}
+
+impl MirrorRule {
+    fn rewrite(&self, url: &str) -> Option<String> {
+        let from = self.from.trim_end_matches('/');
+        let rest = url.strip_prefix(from)?;
+
+        // Only match whole host names and path segments
+        if !rest.is_empty() && !rest.starts_with(['/', '?']) {
+            return None;
+        }
+
+        Some(format!("{}{}", self.to.trim_end_matches('/'), rest))
+    }
+}
+
+/// Replaces the mirror rules which are applied to every download
+pub fn set_mirror_rules(rules: Vec<MirrorRule>) {
+    *MIRROR_RULES.write().unwrap() = rules;
+}
+
+/// Returns the URLs to try in order together with their mirror, the original URL comes last as fallback
+fn candidates(url: &str) -> Vec<(Option<String>, String)> {
+    let failed = FAILED_MIRRORS.read().unwrap();
+    let mut candidates = MIRROR_RULES
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|rule| !failed.contains(&rule.to))
+        .filter_map(|rule| Some((Some(rule.to.clone()), rule.rewrite(url)?)))
+        .collect::<Vec<_>>();
+
+    candidates.push((None, url.to_string()));
+    candidates
+}
+
+///
+/// Sends a GET request to the mirrors of the URL and falls back to the original URL,
+/// a mirror which fails is not used again during this session.
+///
+pub async fn get_mirrored(
+    url: &str,
+    customize: impl Fn(RequestBuilder) -> RequestBuilder,
+) -> Result<Response> {
+    request_mirrored(Method::GET, url, customize).await
+}
+
+/// Same as [get_mirrored], but only requests the headers, e.g. to follow redirects
+pub async fn head_mirrored(
+    url: &str,
+    customize: impl Fn(RequestBuilder) -> RequestBuilder,
+) -> Result<Response> {
+    request_mirrored(Method::HEAD, url, customize).await
+}
+
+async fn request_mirrored(
+    method: Method,
+    url: &str,
+    customize: impl Fn(RequestBuilder) -> RequestBuilder,
+) -> Result<Response> {
+    let mut last_error = None;
+
+    for (mirror, candidate) in candidates(url) {
+        let result = customize(HTTP_CLIENT.request(method.clone(), &candidate))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match (result, mirror) {
+            (Ok(response), _) => {
+                debug!("Requested {} from {}", url, candidate);
+                return Ok(response);
+            }
+            (Err(e), Some(mirror)) => {
+                warn!("Mirror {} failed, falling back: {}", candidate, e);
+
+                // Client errors only concern this file, the mirror itself is still working
+                if !e.status().is_some_and(|status| status.is_client_error()) {
+                    FAILED_MIRRORS.write().unwrap().insert(mirror);
+                }
+                last_error = Some(e);
+            }
+            (Err(e), None) => last_error = Some(e),
+        }
+    }
+
+    Err(last_error
+        .map(Into::into)
+        .unwrap_or_else(|| anyhow!("no URL to request {}", url)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::app::client_api::mock_api::{unreachable_url, MockApi};
+
+    /// The mirror rules are global, tests which set them must not run at the same time
+    static RULES: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    fn rule(from: &str, to: &str) -> MirrorRule {
+        MirrorRule {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    fn is_failed(mirror: &str) -> bool {
+        FAILED_MIRRORS.read().unwrap().contains(mirror)
+    }
+
+    #[test]
+    fn rewrites_prefix() {
+        let rule = rule("https://libraries.minecraft.net/", "https://mirror.example/maven/");
+
+        assert_eq!(
+            rule.rewrite("https://libraries.minecraft.net/com/mojang/brigadier.jar").as_deref(),
+            Some("https://mirror.example/maven/com/mojang/brigadier.jar")
+        );
+        assert_eq!(
+            rule.rewrite("https://libraries.minecraft.net?page=1").as_deref(),
+            Some("https://mirror.example/maven?page=1")
+        );
+        assert_eq!(
+            rule.rewrite("https://libraries.minecraft.net").as_deref(),
+            Some("https://mirror.example/maven")
+        );
+    }
+
+    #[test]
+    fn matches_whole_host_names_only() {
+        let rule = rule("https://libraries.minecraft.net", "https://mirror.example/maven");
+
+        assert!(rule.rewrite("https://libraries.minecraft.net.example.com/x.jar").is_none());
+        assert!(rule.rewrite("https://resources.download.minecraft.net/x").is_none());
+        assert!(rule.rewrite("http://libraries.minecraft.net/x.jar").is_none());
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_server_error() {
+        let _rules = RULES.lock().await;
+        let original = MockApi::start();
+        let mirror = MockApi::builder().status("/", 503).start();
+        set_mirror_rules(vec![rule(&original.url(), &mirror.url())]);
+
+        let response = get_mirrored(&format!("{}/", original.url()), |request| request).await;
+
+        assert_eq!(response.unwrap().text().await.unwrap(), "\"LiquidBounce API\"");
+        assert_eq!(mirror.requests(), vec!["/"]);
+        assert_eq!(original.requests(), vec!["/"]);
+        assert!(is_failed(&mirror.url()));
+
+        // The failed mirror is skipped from then on
+        get_mirrored(&format!("{}/", original.url()), |request| request).await.unwrap();
+        assert_eq!(mirror.requests(), vec!["/"]);
+        assert_eq!(original.requests(), vec!["/", "/"]);
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_connection_error() {
+        let _rules = RULES.lock().await;
+        let original = MockApi::start();
+        let mirror = unreachable_url();
+        set_mirror_rules(vec![rule(&original.url(), &mirror)]);
+
+        let response = get_mirrored(&format!("{}/", original.url()), |request| request).await;
+
+        assert!(response.is_ok());
+        assert_eq!(original.requests(), vec!["/"]);
+        assert!(is_failed(&mirror));
+    }
+
+    #[tokio::test]
+    async fn client_error_keeps_mirror() {
+        let _rules = RULES.lock().await;
+        let original = MockApi::start();
+        let mirror = MockApi::builder().status("/", 404).start();
+        set_mirror_rules(vec![rule(&original.url(), &mirror.url())]);
+
+        for _ in 0..2 {
+            get_mirrored(&format!("{}/", original.url()), |request| request).await.unwrap();
+        }
+
+        // Only the file was missing, the mirror is asked again for the next one
+        assert!(!is_failed(&mirror.url()));
+        assert_eq!(mirror.requests(), vec!["/", "/"]);
+        assert_eq!(original.requests(), vec!["/", "/"]);
+    }
+}
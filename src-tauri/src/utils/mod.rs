@@ -24,9 +24,10 @@ mod extract;
 mod hosts;
 mod macros;
 mod maven;
+mod mirror;
 mod sys;
 
-pub use {checksum::*, download::*, extract::*, maven::*, sys::*};
+pub use {checksum::*, download::*, extract::*, maven::*, mirror::*, sys::*};
 
 #[cfg(windows)]
 pub use hosts::*;
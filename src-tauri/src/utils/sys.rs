@@ -16,12 +16,12 @@
  * You should have received a copy of the GNU General Public License
  * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
  */
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use once_cell::sync::Lazy;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::env::consts;
 use std::fmt::Display;
-use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+use sysinfo::{MemoryRefreshKind, ProcessRefreshKind, RefreshKind, System, UpdateKind};
 
 use std::fs;
 use std::path::Path;
@@ -36,6 +36,24 @@ pub fn sys_memory() -> u64 {
     sys.total_memory()
 }
 
+/// Checks if any process, including ones not started by this launcher, runs from or is given a path inside `path`
+pub fn is_path_in_use(path: &Path) -> bool {
+    let sys = System::new_with_specifics(
+        RefreshKind::nothing().with_processes(
+            ProcessRefreshKind::nothing()
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_cmd(UpdateKind::OnlyIfNotSet),
+        ),
+    );
+    let needle = path.to_string_lossy();
+    let current = sysinfo::get_current_pid().ok();
+
+    sys.processes().iter().filter(|(pid, _)| Some(**pid) != current).any(|(_, process)| {
+        process.exe().is_some_and(|exe| exe.starts_with(path))
+            || process.cmd().iter().any(|arg| arg.to_string_lossy().contains(needle.as_ref()))
+    })
+}
+
 pub const OS: OperatingSystem = if cfg!(target_os = "windows") {
     OperatingSystem::WINDOWS
 } else if cfg!(target_os = "macos") {
@@ -68,7 +86,7 @@ pub enum OperatingSystem {
     UNKNOWN,
 }
 
-#[derive(Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Architecture {
     #[serde(rename = "x86")]
     X86,
@@ -127,6 +145,42 @@ impl OperatingSystem {
         })
     }
 
+    pub fn get_microsoft_name(&self) -> Result<&'static str> {
+        Ok(match self {
+            OperatingSystem::WINDOWS => "windows",
+            OperatingSystem::LINUX => "linux",
+            OperatingSystem::OSX => "macos",
+            _ => bail!("Unsupported operating system for Microsoft OpenJDK"),
+        })
+    }
+
+    pub fn get_corretto_name(&self) -> Result<&'static str> {
+        Ok(match self {
+            OperatingSystem::WINDOWS => "windows",
+            OperatingSystem::LINUX => "linux",
+            OperatingSystem::OSX => "macos",
+            _ => bail!("Unsupported operating system for Amazon Corretto"),
+        })
+    }
+
+    pub fn get_liberica_name(&self) -> Result<&'static str> {
+        Ok(match self {
+            OperatingSystem::WINDOWS => "windows",
+            OperatingSystem::LINUX => "linux",
+            OperatingSystem::OSX => "macos",
+            _ => bail!("Unsupported operating system for Liberica runtime"),
+        })
+    }
+
+    pub fn get_sapmachine_name(&self) -> Result<&'static str> {
+        Ok(match self {
+            OperatingSystem::WINDOWS => "windows",
+            OperatingSystem::LINUX => "linux",
+            OperatingSystem::OSX => "macos",
+            _ => bail!("Unsupported operating system for SapMachine"),
+        })
+    }
+
     pub fn get_archive_type(&self) -> Result<&'static str> {
         Ok(match self {
             OperatingSystem::WINDOWS => "zip",
@@ -161,6 +215,43 @@ impl Architecture {
             _ => bail!("Unsupported architecture for Zulu runtime"),
         })
     }
+
+    pub fn get_microsoft_name(&self) -> Result<&'static str> {
+        Ok(match self {
+            Architecture::X64 => "x64",
+            Architecture::AARCH64 => "aarch64",
+            _ => bail!("Unsupported architecture for Microsoft OpenJDK"),
+        })
+    }
+
+    pub fn get_corretto_name(&self) -> Result<&'static str> {
+        Ok(match self {
+            Architecture::X86 => "x86",
+            Architecture::X64 => "x64",
+            Architecture::ARM => "arm",
+            Architecture::AARCH64 => "aarch64",
+            _ => bail!("Unsupported architecture for Amazon Corretto"),
+        })
+    }
+
+    /// Liberica names the architecture family and passes the word size separately
+    pub fn get_liberica_name(&self) -> Result<(&'static str, u8)> {
+        Ok(match self {
+            Architecture::X86 => ("x86", 32),
+            Architecture::X64 => ("x86", 64),
+            Architecture::ARM => ("arm", 32),
+            Architecture::AARCH64 => ("arm", 64),
+            _ => bail!("Unsupported architecture for Liberica runtime"),
+        })
+    }
+
+    pub fn get_sapmachine_name(&self) -> Result<&'static str> {
+        Ok(match self {
+            Architecture::X64 => "x64",
+            Architecture::AARCH64 => "aarch64",
+            _ => bail!("Unsupported architecture for SapMachine"),
+        })
+    }
 }
 
 impl Display for Architecture {
@@ -192,4 +283,41 @@ pub fn clean_directory(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+///
+/// Waits for an exclusive lock on the file, which is created if missing.
+///
+/// The lock is shared with other processes, e.g. the launcher window and the command line,
+/// and is released once the returned file is dropped.
+///
+pub async fn lock_file(path: &Path) -> Result<fs::File> {
+    let path = path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        file.lock()
+            .with_context(|| format!("Failed to lock {}", path.display()))?;
+        Ok(file)
+    })
+    .await?
+}
+
+/// Returns the size of a file, or of everything in a directory
+pub fn disk_usage(path: &Path) -> u64 {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|entry| disk_usage(&entry.path())).sum())
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
@@ -0,0 +1,290 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Runs the command line mode of the launcher against the local mock API.
+
+// The file downloads of the mock are only used by the unit tests
+#[allow(dead_code)]
+mod mock_api;
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use mock_api::{unreachable_url, MockApi};
+
+const BUILDS_RELEASE: &str = "/api/v1/version/builds/nextgen/release";
+const BUILDS_NIGHTLY: &str = "/api/v1/version/builds/nextgen";
+const SESSION_TOKEN: &str = "mocksessiontoken";
+
+/// Runs the launcher with the given endpoints, with empty data and config directories
+fn run_launcher(endpoints: &[String], args: &[&str]) -> Output {
+    run_in_home(endpoints, None, args)
+}
+
+/// Same as [run_launcher], with the given `options.json` in the config directory
+fn run_launcher_with_options(endpoints: &[String], options: &str, args: &[&str]) -> Output {
+    run_in_home(endpoints, Some(options), args)
+}
+
+fn run_in_home(endpoints: &[String], options: Option<&str>, args: &[&str]) -> Output {
+    static RUN: AtomicU32 = AtomicU32::new(0);
+
+    let home: PathBuf = std::env::temp_dir().join(format!(
+        "liquidlauncher-test-{}-{}",
+        std::process::id(),
+        RUN.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    if let Some(options) = options {
+        std::fs::create_dir_all(home.join("config")).unwrap();
+        std::fs::write(home.join("config").join("options.json"), options).unwrap();
+    }
+
+    // Overrides the platform directories, which only follow HOME and XDG_* on Linux
+    let output = Command::new(env!("CARGO_BIN_EXE_liquidlauncher"))
+        .args(args)
+        .env("LIQUIDLAUNCHER_API", endpoints.join(","))
+        .env("LIQUIDLAUNCHER_DATA_DIR", home.join("data"))
+        .env("LIQUIDLAUNCHER_CONFIG_DIR", home.join("config"))
+        .env("NO_PROXY", "127.0.0.1")
+        .output()
+        .expect("unable to run launcher");
+
+    let _ = std::fs::remove_dir_all(&home);
+    output
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+/// Options with a known session token, which mirror the metadata of Mojang and Fabric to the mock API
+fn mirrored_options(api: &MockApi) -> String {
+    format!(
+        r#"{{
+            "start": {{ "javaDistribution": {{ "type": "manual", "value": "temurin" }} }},
+            "version": {{}},
+            "launcher": {{
+                "showNightlyBuilds": false,
+                "concurrentDownloads": 4,
+                "keepLauncherOpen": false,
+                "sessionToken": "{token}",
+                "mirrors": [
+                    {{ "from": "https://piston-meta.mojang.com", "to": "{api}/mojang" }},
+                    {{ "from": "https://meta.fabricmc.net", "to": "{api}/fabric" }}
+                ]
+            }},
+            "premium": {{}}
+        }}"#,
+        token = SESSION_TOKEN,
+        api = api.url()
+    )
+}
+
+fn path(value: &serde_json::Value) -> &Path {
+    Path::new(value.as_str().expect("expected a path"))
+}
+
+#[test]
+fn lookup_uses_first_reachable_endpoint() {
+    let first = MockApi::start();
+    let second = MockApi::start();
+
+    let output = run_launcher(&[first.url(), second.url()], &["list-builds"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(first.requests(), vec!["/", BUILDS_RELEASE]);
+    assert!(second.requests().is_empty());
+}
+
+#[test]
+fn lookup_falls_back_in_order() {
+    let failing = MockApi::builder().status("/", 503).start();
+    let working = MockApi::start();
+
+    let output = run_launcher(
+        &[unreachable_url(), failing.url(), working.url()],
+        &["list-builds"],
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(failing.requests(), vec!["/"]);
+    assert_eq!(working.requests(), vec!["/", BUILDS_RELEASE]);
+}
+
+#[test]
+fn lookup_fails_without_reachable_endpoint() {
+    let failing = MockApi::builder().status("/", 500).start();
+
+    let output = run_launcher(&[unreachable_url(), failing.url()], &["list-builds"]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Unable to connect to LiquidBounce API"));
+    assert!(stderr(&output).contains("returned status code: 500"));
+}
+
+#[test]
+fn non_ssl_endpoint_is_reported() {
+    let api = MockApi::start();
+
+    let output = run_launcher(&[api.url()], &["list-builds"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Falling back to Non-SSL"));
+}
+
+#[test]
+fn builds_are_listed() {
+    let api = MockApi::start();
+
+    let output = run_launcher(&[api.url()], &["list-builds", "--nightly"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(api.requests(), vec!["/", BUILDS_NIGHTLY]);
+
+    let lines = stdout(&output).lines().map(str::to_string).collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("2\t0.2.0\t1.21.4\tnextgen\trelease"));
+    assert!(lines[1].starts_with("1\t0.1.0\t1.21.4\tnextgen\tnightly"));
+}
+
+#[test]
+fn error_status_of_builds_is_reported() {
+    let api = MockApi::builder()
+        .status("/api/v1/version/builds", 500)
+        .start();
+
+    let output = run_launcher(&[api.url()], &["list-builds"]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("500"));
+    // The request is retried before giving up
+    assert!(api.requests().iter().filter(|x| *x == BUILDS_RELEASE).count() > 1);
+}
+
+#[test]
+fn mods_of_build_are_listed() {
+    let api = MockApi::start();
+
+    let output = run_launcher(&[api.url()], &["list-mods", "--build", "2"]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(api.requests().contains(&"/api/v1/version/launch/2".to_string()));
+    assert!(api
+        .requests()
+        .contains(&"/api/v1/version/mods/1.21.4/fabric".to_string()));
+    assert_eq!(stdout(&output), "Sodium\tenabled\nIris\tdisabled\n");
+}
+
+#[test]
+fn missing_build_is_reported() {
+    let api = MockApi::start();
+
+    let output = run_launcher(&[api.url()], &["list-mods", "--build", "9"]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("404"));
+}
+
+#[test]
+fn unknown_command_is_reported() {
+    let api = MockApi::start();
+
+    let output = run_launcher(&[api.url()], &["list-build"]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Unknown command: list-build"));
+    assert!(stderr(&output).contains("Usage: liquidlauncher"));
+    assert!(api.requests().is_empty());
+}
+
+#[test]
+fn unknown_account_is_reported() {
+    let api = MockApi::start();
+
+    let output = run_launcher(&[api.url()], &["launch", "--account", "Tester", "--dry-run"]);
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Account Tester is not stored"));
+    assert!(api.requests().is_empty());
+}
+
+#[test]
+fn dry_run_prints_redacted_plan() {
+    let api = MockApi::start();
+
+    let output = run_launcher_with_options(
+        &[api.url()],
+        &mirrored_options(&api),
+        &["launch", "--build", "2", "--offline-account", "Tester", "--dry-run"],
+    );
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(api
+        .requests()
+        .contains(&"/fabric/v2/versions/loader/1.21.4/0.16.9/profile/json".to_string()));
+    assert!(api.requests().contains(&"/mojang/v1/packages/1.21.4.json".to_string()));
+
+    // Neither the directories of this run nor any secret end up in the plan
+    let stdout = stdout(&output);
+    assert!(!stdout.contains("liquidlauncher-test-"));
+    assert!(!stdout.contains(SESSION_TOKEN));
+
+    let plan: serde_json::Value = serde_json::from_str(&stdout).expect("plan is not JSON");
+    let version_id = "fabric-loader-0.16.9-1.21.4";
+    assert_eq!(plan["versionId"], version_id);
+    assert_eq!(plan["mainClass"], "net.fabricmc.loader.impl.launch.knot.KnotClient");
+    assert_eq!(plan["java"]["distribution"], "temurin");
+    assert_eq!(plan["java"]["binary"], serde_json::Value::Null);
+    assert_eq!(plan["java"]["downloadRequired"], true);
+    assert_eq!(path(&plan["gameDirectory"]), Path::new("gameDir/nextgen"));
+    assert_eq!(
+        path(&plan["clientJar"]["path"]),
+        Path::new("versions").join(version_id).join(format!("{}.jar", version_id))
+    );
+
+    let mods = plan["mods"].as_array().unwrap();
+    let names = mods.iter().map(|x| x["name"].as_str().unwrap()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["Fabric API", "Sodium"]);
+    assert!(mods.iter().all(|x| path(&x["path"]).starts_with("mod_cache")));
+
+    let class_path = plan["classPath"].as_array().unwrap();
+    assert_eq!(class_path.len(), 3);
+    assert!(class_path.iter().all(|x| path(x).is_relative()));
+
+    let arguments = plan["arguments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x.as_str().unwrap())
+        .collect::<Vec<_>>();
+    let value_of = |name: &str| arguments[arguments.iter().position(|x| *x == name).unwrap() + 1];
+
+    assert_eq!(value_of("--username"), "Tester");
+    assert_eq!(value_of("--accessToken"), "-");
+    assert_eq!(value_of("--xuid"), "<redacted>");
+    assert!(arguments.contains(&"-Dnet.ccbluex.liquidbounce.api.token=<redacted>"));
+    assert!(value_of("--gameDir").starts_with("${data_directory}"));
+    assert!(value_of("-cp").starts_with("${data_directory}"));
+}
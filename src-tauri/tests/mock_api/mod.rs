@@ -0,0 +1,338 @@
+/*
+ * This file is part of LiquidLauncher (https://github.com/CCBlueX/LiquidLauncher)
+ *
+ * Copyright (c) 2015 - 2026 CCBlueX
+ *
+ * LiquidLauncher is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * LiquidLauncher is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with LiquidLauncher. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Local stand-in for the LiquidBounce API, serving the JSON fixtures of `tests/fixtures`.
+//!
+//! Point the launcher at it with the `LIQUIDLAUNCHER_API` environment variable.
+//! The metadata of Mojang and Fabric is served below `/mojang` and `/fabric`, for mirror rules pointing there.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once};
+use std::thread;
+
+pub struct MockApi {
+    address: SocketAddr,
+    requests: Arc<Mutex<Vec<String>>>,
+    ranges: Arc<Mutex<Vec<String>>>,
+}
+
+#[derive(Default)]
+pub struct MockApiBuilder {
+    status_overrides: HashMap<String, u16>,
+    files: HashMap<String, MockFile>,
+}
+
+/// File which is served with an ETag and supports resuming it with `Range` and `If-Range`
+struct MockFile {
+    content: Vec<u8>,
+    etag: String,
+    /// Number of bytes after which the first response is cut off
+    interrupt_after: Option<usize>,
+    interrupted: AtomicBool,
+}
+
+/// Path of a request and the headers the mock looks at
+struct Request {
+    path: String,
+    range: Option<String>,
+    if_range: Option<String>,
+    authorized: bool,
+}
+
+impl MockApiBuilder {
+    /// Answers every request whose path starts with the prefix with the given status code
+    pub fn status(mut self, path_prefix: &str, status: u16) -> Self {
+        self.status_overrides.insert(path_prefix.to_string(), status);
+        self
+    }
+
+    /// Serves the content at the path, byte ranges are answered as long as `If-Range` matches the ETag
+    pub fn file(mut self, path: &str, content: &[u8], etag: &str) -> Self {
+        self.files.insert(
+            path.to_string(),
+            MockFile {
+                content: content.to_vec(),
+                etag: etag.to_string(),
+                interrupt_after: None,
+                interrupted: AtomicBool::new(false),
+            },
+        );
+        self
+    }
+
+    /// Closes the connection of the first response of the file after the given number of bytes
+    pub fn interrupt(mut self, path: &str, after: usize) -> Self {
+        let file = self.files.get_mut(path).expect("file has to be added first");
+        file.interrupt_after = Some(after);
+        self
+    }
+
+    pub fn start(self) -> MockApi {
+        // The mock API is local, a system proxy must not be used for it
+        static NO_PROXY: Once = Once::new();
+        NO_PROXY.call_once(|| std::env::set_var("NO_PROXY", "127.0.0.1"));
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("unable to bind mock API");
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let ranges = Arc::new(Mutex::new(vec![]));
+        let status_overrides = Arc::new(self.status_overrides);
+        let files = Arc::new(self.files);
+
+        thread::spawn({
+            let requests = requests.clone();
+            let ranges = ranges.clone();
+            move || {
+                for stream in listener.incoming().flatten() {
+                    let requests = requests.clone();
+                    let ranges = ranges.clone();
+                    let status_overrides = status_overrides.clone();
+                    let files = files.clone();
+                    thread::spawn(move || {
+                        handle(stream, &requests, &ranges, &status_overrides, &files)
+                    });
+                }
+            }
+        });
+
+        MockApi {
+            address,
+            requests,
+            ranges,
+        }
+    }
+}
+
+impl MockApi {
+    pub fn builder() -> MockApiBuilder {
+        MockApiBuilder::default()
+    }
+
+    pub fn start() -> Self {
+        Self::builder().start()
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Paths which have been requested so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// `Range` headers which have been sent so far, in order
+    pub fn ranges(&self) -> Vec<String> {
+        self.ranges.lock().unwrap().clone()
+    }
+}
+
+/// Returns the address of a port nobody listens on
+pub fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+
+    let mut request = Request {
+        path: request_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or("/")
+            .to_string(),
+        range: None,
+        if_range: None,
+        authorized: false,
+    };
+
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).unwrap_or(0) == 0 || header.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "authorization" => request.authorized = true,
+            "range" => request.range = Some(value.trim().to_string()),
+            "if-range" => request.if_range = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    Some(request)
+}
+
+fn handle(
+    mut stream: TcpStream,
+    requests: &Mutex<Vec<String>>,
+    ranges: &Mutex<Vec<String>>,
+    status_overrides: &HashMap<String, u16>,
+    files: &HashMap<String, MockFile>,
+) {
+    let Some(request) = read_request(&stream) else {
+        return;
+    };
+    let path = request.path.clone();
+    requests.lock().unwrap().push(path.clone());
+    if let Some(range) = &request.range {
+        ranges.lock().unwrap().push(range.clone());
+    }
+
+    if let Some(file) = files.get(&path) {
+        serve_file(stream, file, &request);
+        return;
+    }
+
+    let override_status = status_overrides
+        .iter()
+        .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+        .map(|(_, status)| *status);
+
+    let (status, body) = match override_status {
+        Some(status) => (status, format!("{{\"error\":\"status {}\"}}", status)),
+        None => route(&path, request.authorized),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+///
+/// Answers with the whole file, or with the requested range if the file has not changed since, see RFC 9110 13.1.5
+///
+fn serve_file(mut stream: TcpStream, file: &MockFile, request: &Request) {
+    let length = file.content.len();
+    let start = request
+        .range
+        .as_deref()
+        .filter(|_| request.if_range.as_deref() == Some(file.etag.as_str()))
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.strip_suffix('-'))
+        .and_then(|start| start.parse::<usize>().ok());
+
+    let head = |status: u16, extra: String, content_length: usize| {
+        format!(
+            "HTTP/1.1 {} {}\r\nAccept-Ranges: bytes\r\nETag: {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            reason(status),
+            file.etag,
+            extra,
+            content_length
+        )
+    };
+
+    let (head, body) = match start {
+        Some(start) if start >= length => (
+            head(416, format!("Content-Range: bytes */{}\r\n", length), 0),
+            &[][..],
+        ),
+        Some(start) => (
+            head(
+                206,
+                format!("Content-Range: bytes {}-{}/{}\r\n", start, length - 1, length),
+                length - start,
+            ),
+            &file.content[start..],
+        ),
+        None => (head(200, String::new(), length), &file.content[..]),
+    };
+
+    let _ = stream.write_all(head.as_bytes());
+    match file.interrupt_after {
+        // Drops the connection before the announced length is reached
+        Some(after) if !file.interrupted.swap(true, Ordering::SeqCst) => {
+            let _ = stream.write_all(&body[..after.min(body.len())]);
+            let _ = stream.flush();
+        }
+        _ => {
+            let _ = stream.write_all(body);
+        }
+    }
+}
+
+fn route(path: &str, authorized: bool) -> (u16, String) {
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+    let fixture = match segments.as_slice() {
+        [""] => return (200, "\"LiquidBounce API\"".to_string()),
+        ["api", "v1", "version", "builds", ..] => "builds.json".to_string(),
+        ["api", "v1", "version", "launch", id] => format!("launch_{}.json", id),
+        ["api", "v1", "version", "mods", _, _] => "mods.json".to_string(),
+        ["api", "v1", "version", "changelog", _] => "changelog.json".to_string(),
+        ["api", "v3", "blog"] => {
+            let page = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("page="))
+                .unwrap_or("1");
+            format!("blog_{}.json", page)
+        }
+        ["api", "v3", "oauth", "user"] | ["api", "v3", "file", "resolve", _] if !authorized => {
+            return (401, "{\"error\":\"unauthorized\"}".to_string())
+        }
+        ["api", "v3", "oauth", "user"] => "user.json".to_string(),
+        ["api", "v3", "file", "resolve", _] => "resolve.json".to_string(),
+        ["mojang", "mc", "game", "version_manifest_v2.json"] => "version_manifest.json".to_string(),
+        ["mojang", "v1", "packages", profile] => format!("version_{}", profile),
+        ["fabric", "v2", "versions", "loader", _, _, "profile", "json"] => "fabric_profile.json".to_string(),
+        _ => return (404, "{\"error\":\"not found\"}".to_string()),
+    };
+
+    match fs::read_to_string(fixtures().join(&fixture)) {
+        Ok(content) => (200, content),
+        Err(_) => (404, "{\"error\":\"not found\"}".to_string()),
+    }
+}
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        206 => "Partial Content",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        416 => "Range Not Satisfiable",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}